@@ -1,22 +1,176 @@
+import argparse
+import json
 import signal
 import sys
+import tempfile
+import threading
+import time
+from datetime import datetime
+from pathlib import Path
 
 from PySide6 import QtCore, QtWidgets
 
-from .auth import get_oauth_token
-from .config import PERF_LOG_FILE
-from .perf_log import perf_timer, setup_perf_logger
-from .qt_utils import configure_qt_plugins
-from .storage import load_saved_state, save_state
-from .stream_resolver import resolve_channel_urls
+from .auth import clear_cached_token, get_oauth_token
+from .chat_log import ChatLogger
+from .commentary import CommentaryInput, CommentaryStream
+from .config import MARKERS_FILE, PERF_LOG_FILE, QUALITY_STEPS
+from .history import record_history_event
+from .hls import fetch_playlist, media_playlist_has_ad
+from .hooks import HookController, ScriptHooks
+from .i18n import DEFAULT_LOCALE, set_locale
+from .inhibit import ScreenSaverInhibitor
+from .music_bed import MusicBed
+from .perf_log import log_perf, perf_timer, setup_perf_logger
+from .qt_utils import (
+    MEDIA_BACKENDS,
+    configure_media_backend,
+    configure_qt_plugins,
+)
+from .startup_profile import StartupProfiler
+from .storage import (
+    load_saved_state,
+    load_session_state,
+    save_session_state,
+    save_state,
+)
+from .stream_resolver import (
+    RetryPolicy,
+    StreamEntry,
+    StreamMetadata,
+    get_stream_metadata,
+    invalidate_channel,
+    mark_channel_failed,
+    refresh_stream_metadata,
+    resolve_channel_urls,
+)
+from .sync import (
+    DEFAULT_SYNC_ADDRESS,
+    DEFAULT_SYNC_PORT,
+    SYNC_ROLES,
+    SyncFollower,
+    SyncLeader,
+)
+from .test_source import test_channel_names, test_stream_entries
+from .udp_control import UdpControl
 from .ui.control_panel import ControlPanelWindow
+from .ui.overlay_theme import OverlayTheme
 from .ui.player_window import PlayerWindow
+from .watch_stats import (
+    STATS_FORMATS,
+    WatchTimeTracker,
+    format_watch_stats,
+    load_watch_stats,
+)
+from .webhooks import post_webhook_event
 
 
 class _ResolveWorkerSignals(QtCore.QObject):
     finished = QtCore.Signal(list, bool, int)
 
 
+class _AdCheckWorkerSignals(QtCore.QObject):
+    finished = QtCore.Signal(str, str, bool)
+
+
+class _AdCheckWorker(QtCore.QRunnable):
+    def __init__(self, channel: str, url: str) -> None:
+        super().__init__()
+        self._channel = channel
+        self._url = url
+        self.signals = _AdCheckWorkerSignals()
+
+    def run(self) -> None:
+        try:
+            playlist = fetch_playlist(self._url, timeout=4.0)
+            has_ad = media_playlist_has_ad(playlist)
+        except Exception as exc:
+            log_perf(
+                "app.ad_check_failed",
+                channel=self._channel,
+                error=type(exc).__name__,
+            )
+            has_ad = False
+        self.signals.finished.emit(self._channel, self._url, has_ad)
+
+
+class _MetadataWorkerSignals(QtCore.QObject):
+    finished = QtCore.Signal(dict)
+
+
+class _MetadataWorker(QtCore.QRunnable):
+    def __init__(
+        self,
+        channels: list[str],
+        oauth_token: str,
+        timeout_sec: float,
+    ) -> None:
+        super().__init__()
+        self._channels = list(channels)
+        self._oauth_token = oauth_token
+        self._timeout_sec = timeout_sec
+        self.signals = _MetadataWorkerSignals()
+
+    def run(self) -> None:
+        metadata: dict[str, StreamMetadata] = {}
+        for channel in self._channels:
+            try:
+                result = refresh_stream_metadata(
+                    channel,
+                    self._oauth_token,
+                    self._timeout_sec,
+                )
+            except Exception as exc:
+                log_perf(
+                    "app.metadata_failed",
+                    channel=channel,
+                    error=type(exc).__name__,
+                )
+                result = get_stream_metadata(channel)
+            if result is not None:
+                metadata[channel] = result
+        self.signals.finished.emit(metadata)
+
+
+class _CommentaryResolveWorkerSignals(QtCore.QObject):
+    finished = QtCore.Signal(str, str)
+
+
+class _CommentaryResolveWorker(QtCore.QRunnable):
+    def __init__(self, channel: str, oauth_token: str, timeout_sec: float) -> None:
+        super().__init__()
+        self._channel = channel
+        self._oauth_token = oauth_token
+        self._timeout_sec = timeout_sec
+        self.signals = _CommentaryResolveWorkerSignals()
+
+    def run(self) -> None:
+        # Only the audio is played, so the smallest rendition will do.
+        entries = resolve_channel_urls(
+            [self._channel],
+            self._oauth_token,
+            max_quality=QUALITY_STEPS[0],
+            timeout_sec=self._timeout_sec,
+        )
+        url = entries[0].url if entries else ""
+        self.signals.finished.emit(self._channel, url)
+
+
+class _WebhookWorker(QtCore.QRunnable):
+    def __init__(
+        self,
+        urls: list[str],
+        event: str,
+        fields: dict[str, object],
+    ) -> None:
+        super().__init__()
+        self._urls = list(urls)
+        self._event = event
+        self._fields = dict(fields)
+
+    def run(self) -> None:
+        post_webhook_event(self._urls, self._event, **self._fields)
+
+
 class _ResolveWorker(QtCore.QRunnable):
     def __init__(
         self,
@@ -25,6 +179,11 @@ class _ResolveWorker(QtCore.QRunnable):
         focused: bool,
         request_id: int,
         max_quality: int,
+        quality_overrides: dict[str, int],
+        retry_policy: RetryPolicy,
+        retry_overrides: dict[str, RetryPolicy],
+        timeout_sec: float,
+        max_bandwidth_kbps: int,
     ) -> None:
         super().__init__()
         self._channels = list(channels)
@@ -32,8 +191,17 @@ class _ResolveWorker(QtCore.QRunnable):
         self._focused = focused
         self._request_id = request_id
         self._max_quality = max_quality
+        self._quality_overrides = dict(quality_overrides)
+        self._retry_policy = retry_policy
+        self._retry_overrides = dict(retry_overrides)
+        self._timeout_sec = timeout_sec
+        self._max_bandwidth_kbps = max_bandwidth_kbps
+        self._cancel_event = threading.Event()
         self.signals = _ResolveWorkerSignals()
 
+    def cancel(self) -> None:
+        self._cancel_event.set()
+
     def run(self) -> None:
         with perf_timer(
             "resolve_channel_urls",
@@ -46,7 +214,15 @@ class _ResolveWorker(QtCore.QRunnable):
                 self._channels,
                 self._oauth_token,
                 max_quality=self._max_quality,
+                quality_overrides=self._quality_overrides,
+                retry_policy=self._retry_policy,
+                timeout_sec=self._timeout_sec,
+                max_bandwidth_kbps=self._max_bandwidth_kbps or None,
+                cancel_event=self._cancel_event,
+                retry_overrides=self._retry_overrides,
             )
+        if self._cancel_event.is_set():
+            return
         self.signals.finished.emit(
             resolved,
             self._focused,
@@ -54,14 +230,191 @@ class _ResolveWorker(QtCore.QRunnable):
         )
 
 
-def main() -> int:
-    oauth_token = get_oauth_token()
-    saved_streams, settings = load_saved_state()
+def _retry_policy_from_settings(settings: dict[str, object]) -> RetryPolicy:
+    attempts = max(1, int(settings.get("resolve_retry_attempts", 3)))
+    backoff_sec = max(
+        0.0, float(settings.get("resolve_retry_backoff_sec", 1.0))
+    )
+    return RetryPolicy(attempts=attempts, backoff_sec=backoff_sec)
+
+
+def _channel_reconnect_policies(
+    settings: dict[str, object],
+) -> dict[str, dict[str, float]]:
+    policies = settings.get("channel_reconnect_policies", {})
+    if not isinstance(policies, dict):
+        return {}
+    parsed: dict[str, dict[str, float]] = {}
+    for channel, policy in policies.items():
+        if not isinstance(policy, dict):
+            continue
+        values: dict[str, float] = {}
+        for key in ("attempts", "backoff_sec", "max_failures"):
+            if key not in policy:
+                continue
+            try:
+                values[key] = max(0.0, float(policy[key]))
+            except (TypeError, ValueError):
+                continue
+        parsed[str(channel).strip().lower()] = values
+    return parsed
+
+
+def _channel_retry_policies(
+    settings: dict[str, object],
+) -> dict[str, RetryPolicy]:
+    default = _retry_policy_from_settings(settings)
+    return {
+        channel: RetryPolicy(
+            attempts=max(1, int(policy.get("attempts", default.attempts))),
+            backoff_sec=float(policy.get("backoff_sec", default.backoff_sec)),
+        )
+        for channel, policy in _channel_reconnect_policies(settings).items()
+        if "attempts" in policy or "backoff_sec" in policy
+    }
+
+
+def _channel_quality_overrides(
+    settings: dict[str, object],
+    fallbacks: dict[str, int],
+) -> dict[str, int]:
+    max_quality = int(settings.get("max_stream_quality", 720))
+    caps = settings.get("channel_max_quality", {})
+    overrides = {
+        str(channel).lower(): min(int(quality), max_quality)
+        for channel, quality in (caps.items() if isinstance(caps, dict) else [])
+        if int(quality) > 0
+    }
+    # A step-down after playback errors may go below the configured cap.
+    for channel, quality in fallbacks.items():
+        overrides[channel] = min(quality, overrides.get(channel, quality))
+    return overrides
+
+
+def _offline_placeholder_channels(
+    settings: dict[str, object],
+    requested: list[str],
+    resolved: list[StreamEntry],
+    manual_layout: bool,
+) -> list[str]:
+    if not manual_layout or not bool(
+        settings.get("show_offline_placeholders", False)
+    ):
+        return []
+    live = {entry.channel.lower() for entry in resolved}
+    return [channel for channel in requested if channel.lower() not in live]
+
+
+def _parse_args(argv: list[str] | None) -> argparse.Namespace:
+    parser = argparse.ArgumentParser(prog="trs")
+    parser.add_argument(
+        "--media-backend",
+        choices=MEDIA_BACKENDS,
+        default="auto",
+        help="force a Qt Multimedia backend instead of the platform default",
+    )
+    parser.add_argument(
+        "--profile-startup",
+        action="store_true",
+        help="report time spent resolving and starting each stream",
+    )
+    parser.add_argument(
+        "--sync",
+        choices=SYNC_ROLES,
+        help="broadcast (leader) or mirror (follower) the active streams",
+    )
+    parser.add_argument("--sync-address", default=DEFAULT_SYNC_ADDRESS)
+    parser.add_argument("--sync-port", type=int, default=DEFAULT_SYNC_PORT)
+    parser.add_argument(
+        "--wid",
+        type=lambda value: int(value, 0),
+        help="render the player inside an existing native window (XID/HWND/NSView)",
+    )
+    parser.add_argument(
+        "--status-json",
+        nargs="?",
+        type=float,
+        const=5.0,
+        metavar="SECONDS",
+        help="print a JSON status line to stdout every SECONDS (default 5)",
+    )
+    parser.add_argument(
+        "--overlay-only",
+        nargs="?",
+        const="#00ff00",
+        metavar="COLOR",
+        help="draw only the overlays over a flat key color (default #00ff00)",
+    )
+    parser.add_argument(
+        "--test-sources",
+        type=int,
+        default=0,
+        metavar="N",
+        help="play N local test patterns instead of Twitch streams",
+    )
+    subparsers = parser.add_subparsers(dest="command")
+    stats_parser = subparsers.add_parser(
+        "stats",
+        help="print cumulative watch time per channel",
+    )
+    stats_parser.add_argument(
+        "--format",
+        choices=STATS_FORMATS,
+        default="table",
+    )
+    subparsers.add_parser(
+        "logout",
+        help="forget the cached Twitch auth token",
+    )
+    return parser.parse_args(argv)
+
+
+def main(argv: list[str] | None = None) -> int:
+    args = _parse_args(argv)
+    if args.command == "stats":
+        print(format_watch_stats(load_watch_stats(), args.format))
+        return 0
+    if args.command == "logout":
+        clear_cached_token()
+        return 0
+
+    profiler = StartupProfiler() if args.profile_startup else None
+    test_mode = args.test_sources > 0
+    oauth_token = "" if test_mode else get_oauth_token()
+    # Test runs only read the real save.json, never normalize it in place.
+    saved_streams, settings = load_saved_state(write_back=not test_mode)
+    save_file: Path | None = None
+    session_file: Path | None = None
+    stats_file: Path | None = None
+    history_file: Path | None = None
+    markers_file = MARKERS_FILE
+    chat_log_dir = Path(str(settings.get("chat_log_dir", "")) or "chat_logs")
+    if test_mode:
+        # Test runs keep the real settings but never write over the real state.
+        scratch_dir = Path(tempfile.mkdtemp(prefix="trs-test-"))
+        save_file = scratch_dir / "save.json"
+        session_file = scratch_dir / "session.json"
+        stats_file = scratch_dir / "watch_stats.json"
+        history_file = scratch_dir / "history.jsonl"
+        markers_file = scratch_dir / "markers.jsonl"
+        chat_log_dir = scratch_dir / "chat_logs"
+        saved_streams = test_channel_names(args.test_sources)
+        settings = {**settings, "paceman_mode": False, "paceman_fallback": False}
+        print(f"test sources: {', '.join(saved_streams)} (state in {scratch_dir})")
+    session_state = load_session_state(saved_streams, session_file)
 
     configure_qt_plugins()
+    configure_media_backend(args.media_backend)
     setup_perf_logger(PERF_LOG_FILE)
     app = QtWidgets.QApplication(sys.argv)
+    if profiler is not None:
+        profiler.mark("qt_ready")
+    set_locale(str(settings.get("locale", DEFAULT_LOCALE)))
     player_window = PlayerWindow()
+    if args.overlay_only and not player_window.set_overlay_only(args.overlay_only):
+        return 2
+    if args.wid is not None and not player_window.embed_into(args.wid):
+        print(f"Could not embed into window {args.wid:#x}; opening standalone.")
     player_window.show()
     initial_manual_columns = max(0, int(settings.get("manual_grid_columns", 0)))
     initial_manual_rows = max(0, int(settings.get("manual_grid_rows", 0)))
@@ -69,44 +422,253 @@ def main() -> int:
         initial_manual_columns,
         initial_manual_rows,
     )
+    player_window.set_fair_grid_fill(bool(settings.get("fair_grid_fill", False)))
+    player_window.set_video_delays(
+        int(settings.get("video_delay_ms", 0)),
+        dict(settings.get("channel_video_delays_ms", {})),
+    )
+    player_window.set_preferred_audio_track(
+        str(settings.get("preferred_audio_track", ""))
+    )
+    player_window.set_subtitles(
+        bool(settings.get("subtitles_enabled", False)),
+        int(settings.get("subtitle_font_size", 20)),
+    )
+    player_window.set_overlay_theme(
+        OverlayTheme.from_dict(settings.get("overlay_theme"))
+    )
+    player_window.set_startup_timeout(
+        float(settings.get("player_startup_timeout_sec", 20.0))
+    )
+    player_window.set_keybindings(dict(settings.get("keybindings", {})))
+    player_window.set_duck_volume(float(settings.get("duck_volume", 0.25)))
+    player_window.set_channel_crops(dict(settings.get("channel_crops", {})))
     manual_mode = not bool(settings.get("paceman_mode", False))
     initial_manual_layout = manual_mode or bool(
         settings.get("paceman_fallback", False)
     )
     initial_streams = list(saved_streams)
     initial_max_quality = int(settings.get("max_stream_quality", 720))
-    player_window.set_streams(
-        resolve_channel_urls(
+    resolve_timings: dict[str, float] = {}
+    saved_mutes = session_state.get("muted")
+    if isinstance(saved_mutes, dict):
+        player_window.restore_channel_mute_states(saved_mutes)
+    if test_mode:
+        initial_resolved = test_stream_entries(initial_streams)
+    else:
+        initial_resolved = resolve_channel_urls(
             initial_streams,
             oauth_token,
             max_quality=initial_max_quality,
-        ),
+            retry_policy=_retry_policy_from_settings(settings),
+            timeout_sec=float(settings.get("resolve_timeout_sec", 20.0)),
+            max_bandwidth_kbps=(
+                int(settings.get("max_stream_bandwidth_kbps", 0)) or None
+            ),
+            timings=resolve_timings,
+            retry_overrides=_channel_retry_policies(settings),
+        )
+    if profiler is not None:
+        profiler.mark("initial_resolve")
+        profiler.set_resolve_times(resolve_timings)
+        profiler.expect_channels([entry.channel for entry in initial_resolved])
+
+        def on_playback_milestone(channel: str, milestone: str) -> None:
+            profiler.mark_channel(channel, milestone)
+            if profiler.complete() and not profiler.reported:
+                print(profiler.report())
+
+        def on_profile_timeout() -> None:
+            if not profiler.reported:
+                print(profiler.report())
+
+        player_window.playback_milestone.connect(on_playback_milestone)
+        QtCore.QTimer.singleShot(60_000, on_profile_timeout)
+    player_window.set_streams(
+        initial_resolved,
         manual_mode=initial_manual_layout,
+        offline_channels=_offline_placeholder_channels(
+            settings,
+            initial_streams,
+            initial_resolved,
+            initial_manual_layout,
+        ),
     )
+    if profiler is not None:
+        profiler.mark("players_started")
 
     control_panel = ControlPanelWindow(saved_streams, settings)
+    control_panel.set_channel_live_states(
+        initial_streams,
+        [entry.channel for entry in initial_resolved],
+    )
     current_streams = list(saved_streams)
     current_settings = dict(settings)
     thread_pool = QtCore.QThreadPool.globalInstance()
     latest_request_id = 0
     request_manual_layout: dict[int, bool] = {}
+    request_channels: dict[int, list[str]] = {}
     pending_resolve_workers: dict[int, _ResolveWorker] = {}
+    offline_channels: set[str] = set()
+    status_errors: list[dict[str, str]] = []
+    quality_fallbacks: dict[str, int] = {}
+    pending_failovers: set[str] = set()
+    channel_failures: dict[str, int] = {}
+
+    def on_channel_milestone(channel: str, milestone: str) -> None:
+        if milestone == "first_frame":
+            # Only consecutive failures count towards max_failures.
+            channel_failures.pop(channel.lower(), None)
+
+    player_window.playback_milestone.connect(on_channel_milestone)
+    script_hooks = ScriptHooks(
+        str(settings.get("hooks_script", "")),
+        HookController(
+            # Deferred, so a hook that changes focus doesn't re-enter itself.
+            focus=lambda channel: QtCore.QTimer.singleShot(
+                0, lambda: control_panel.focus_channel(channel)
+            ),
+            clear_focus=lambda: QtCore.QTimer.singleShot(
+                0, control_panel.clear_focus
+            ),
+            displayed=player_window.displayed_channels,
+            metadata=get_stream_metadata,
+        ),
+    )
+    shown_channels = player_window.displayed_channels()
+    shown_focused = False
+    shown_since = {channel: time.monotonic() for channel in shown_channels}
+    record_history_event(
+        "streams",
+        history_file=history_file,
+        channels=shown_channels,
+        focused=False,
+    )
+    watch_tracker = WatchTimeTracker(stats_file)
+    watch_tracker.update(shown_channels, False)
+    screensaver_inhibitor = ScreenSaverInhibitor()
+
+    def update_screensaver_inhibition() -> None:
+        screensaver_inhibitor.set_active(
+            bool(current_settings.get("inhibit_screensaver", True))
+            and bool(player_window.displayed_channels())
+        )
+
+    update_screensaver_inhibition()
+    chat_logger = ChatLogger(chat_log_dir, app)
+    chat_log_channels: list[str] = list(initial_streams)
+
+    def update_chat_logging() -> None:
+        if bool(current_settings.get("chat_log_enabled", False)):
+            chat_logger.set_channels(chat_log_channels)
+        else:
+            chat_logger.set_channels([])
+
+    update_chat_logging()
+    music_bed = MusicBed(app)
+    music_bed.configure(
+        str(settings.get("music_bed_path", "")),
+        float(settings.get("music_bed_volume", 0.15)),
+    )
+    commentary = CommentaryInput(app)
+    commentary.configure(
+        str(settings.get("commentary_input", "")),
+        float(settings.get("commentary_volume", 1.0)),
+    )
+    commentary_stream = CommentaryStream(app)
+    commentary_stream.set_volume(float(settings.get("commentary_volume", 1.0)))
+    commentary_channel = ""
+    pending_commentary_workers: list[_CommentaryResolveWorker] = []
+
+    def on_commentary_resolved(channel: str, url: str) -> None:
+        pending_commentary_workers.clear()
+        if channel != commentary_channel:
+            # The setting changed while this resolve was in flight.
+            resolve_commentary_stream()
+            return
+        if not url:
+            print(f"commentary channel '{channel}' is offline; retrying in 30s")
+            QtCore.QTimer.singleShot(30_000, resolve_commentary_stream)
+        commentary_stream.set_url(url)
+
+    def resolve_commentary_stream() -> None:
+        if pending_commentary_workers:
+            return
+        if not commentary_channel or test_mode:
+            commentary_stream.set_url("")
+            return
+        invalidate_channel(commentary_channel, oauth_token)
+        worker = _CommentaryResolveWorker(
+            commentary_channel,
+            oauth_token,
+            float(current_settings.get("resolve_timeout_sec", 20.0)),
+        )
+        worker.signals.finished.connect(on_commentary_resolved)
+        pending_commentary_workers.append(worker)
+        thread_pool.start(worker)
+
+    def update_commentary_channel() -> None:
+        nonlocal commentary_channel
+        channel = str(current_settings.get("commentary_channel", "")).strip()
+        if channel.lower() == commentary_channel.lower():
+            return
+        commentary_channel = channel
+        resolve_commentary_stream()
+
+    commentary_stream.failed.connect(
+        lambda _message: QtCore.QTimer.singleShot(
+            5_000, resolve_commentary_stream
+        )
+    )
+    update_commentary_channel()
+
+    def publish_event(event: str, **fields: object) -> None:
+        script_hooks.call(event, **fields)
+        urls = list(current_settings.get("webhook_urls", []))
+        if not urls:
+            return
+        thread_pool.start(_WebhookWorker(urls, event, fields))
+
+    def update_offline_channels(
+        requested: list[str],
+        resolved: list[str],
+    ) -> None:
+        resolved_keys = {channel.lower() for channel in resolved}
+        for channel in requested:
+            channel_key = channel.lower()
+            if channel_key in resolved_keys:
+                if channel_key in offline_channels:
+                    offline_channels.discard(channel_key)
+                    publish_event("online", channel=channel)
+            elif channel_key not in offline_channels:
+                offline_channels.add(channel_key)
+                publish_event("offline", channel=channel)
 
     def on_manual_streams_changed(updated: list[str]) -> None:
         nonlocal current_streams
         current_streams = list(updated)
-        save_state(current_streams, current_settings)
+        save_state(current_streams, current_settings, save_file)
 
     def on_streams_resolved(
         resolved: list[object],
         focused: bool,
         request_id: int,
     ) -> None:
+        nonlocal shown_channels, shown_focused
         pending_resolve_workers.pop(request_id, None)
         manual_mode = request_manual_layout.pop(
             request_id,
             control_panel.is_manual_source_active(),
         )
+        requested_channels = request_channels.pop(request_id, [])
+        resolved_channels = [entry.channel for entry in resolved]
+        control_panel.set_channel_live_states(
+            requested_channels,
+            resolved_channels,
+        )
+        update_offline_channels(requested_channels, resolved_channels)
+        for channel in requested_channels:
+            pending_failovers.discard(channel.lower())
         if request_id != latest_request_id:
             return
         with perf_timer(
@@ -120,18 +682,95 @@ def main() -> int:
                 resolved,
                 focused=focused,
                 manual_mode=manual_mode,
+                offline_channels=_offline_placeholder_channels(
+                    current_settings,
+                    requested_channels,
+                    resolved,
+                    manual_mode,
+                ),
+            )
+        displayed = player_window.displayed_channels()
+        player_window.set_stream_metadata(displayed_metadata(displayed))
+        watch_tracker.update(displayed, focused)
+        update_screensaver_inhibition()
+        shown_focused = focused
+        now = time.monotonic()
+        for channel in list(shown_since):
+            if channel not in displayed:
+                shown_since.pop(channel)
+        for channel in displayed:
+            shown_since.setdefault(channel, now)
+        if displayed != shown_channels:
+            shown_channels = displayed
+            record_history_event(
+                "streams",
+                history_file=history_file,
+                channels=displayed,
+                focused=focused,
             )
 
+    def displayed_metadata(channels: list[str]) -> dict[str, StreamMetadata]:
+        metadata: dict[str, StreamMetadata] = {}
+        for channel in channels:
+            result = get_stream_metadata(channel)
+            if result is not None:
+                metadata[channel] = result
+        return metadata
+
+    player_window.set_stream_metadata(
+        displayed_metadata(player_window.displayed_channels())
+    )
+    pending_metadata_workers: list[_MetadataWorker] = []
+
+    def on_metadata_refreshed(metadata: dict[str, StreamMetadata]) -> None:
+        pending_metadata_workers.clear()
+        displayed = player_window.displayed_channels()
+        player_window.set_stream_metadata(
+            {
+                channel: metadata[channel]
+                for channel in displayed
+                if channel in metadata
+            }
+        )
+
+    def refresh_metadata() -> None:
+        displayed = player_window.displayed_channels()
+        if test_mode or pending_metadata_workers or not displayed:
+            return
+        worker = _MetadataWorker(
+            displayed,
+            oauth_token,
+            float(current_settings.get("resolve_timeout_sec", 20.0)),
+        )
+        worker.signals.finished.connect(on_metadata_refreshed)
+        pending_metadata_workers.append(worker)
+        thread_pool.start(worker)
+
+    def cancel_pending_resolves() -> None:
+        for pending_id, pending_worker in list(pending_resolve_workers.items()):
+            pending_worker.cancel()
+            request_manual_layout.pop(pending_id, None)
+            request_channels.pop(pending_id, None)
+        pending_resolve_workers.clear()
+
     def on_active_streams_changed(
         updated: list[str],
         focused: bool,
         manual_layout: bool,
     ) -> None:
-        nonlocal latest_request_id
+        nonlocal latest_request_id, chat_log_channels
+        cancel_pending_resolves()
+        chat_log_channels = list(updated)
+        update_chat_logging()
         latest_request_id += 1
         request_id = latest_request_id
         request_manual_layout[request_id] = manual_layout
         channels = list(updated)
+        request_channels[request_id] = list(channels)
+        if test_mode:
+            # Test sources never touch the network; anything else reads offline.
+            on_streams_resolved(test_stream_entries(channels), focused, request_id)
+            return
         max_quality = int(current_settings.get("max_stream_quality", 720))
         worker = _ResolveWorker(
             channels,
@@ -139,6 +778,11 @@ def main() -> int:
             focused,
             request_id,
             max_quality,
+            _channel_quality_overrides(current_settings, quality_fallbacks),
+            _retry_policy_from_settings(current_settings),
+            _channel_retry_policies(current_settings),
+            float(current_settings.get("resolve_timeout_sec", 20.0)),
+            int(current_settings.get("max_stream_bandwidth_kbps", 0)),
         )
         worker.signals.finished.connect(on_streams_resolved)
         pending_resolve_workers[request_id] = worker
@@ -152,18 +796,161 @@ def main() -> int:
         previous_manual_rows = max(
             0, int(current_settings.get("manual_grid_rows", 0))
         )
-        current_settings = dict(updated)
+        if updated.get("max_stream_quality") != current_settings.get(
+            "max_stream_quality"
+        ):
+            quality_fallbacks.clear()
+        current_settings = {**current_settings, **updated}
         manual_columns = max(
             0, int(current_settings.get("manual_grid_columns", 0))
         )
         manual_rows = max(0, int(current_settings.get("manual_grid_rows", 0)))
         player_window.set_manual_grid_limits(manual_columns, manual_rows)
-        save_state(current_streams, current_settings)
+        player_window.set_fair_grid_fill(
+            bool(current_settings.get("fair_grid_fill", False))
+        )
+        player_window.set_video_delays(
+            int(current_settings.get("video_delay_ms", 0)),
+            dict(current_settings.get("channel_video_delays_ms", {})),
+        )
+        player_window.set_overlay_theme(
+            OverlayTheme.from_dict(current_settings.get("overlay_theme"))
+        )
+        player_window.set_startup_timeout(
+            float(current_settings.get("player_startup_timeout_sec", 20.0))
+        )
+        player_window.set_channel_crops(
+            dict(current_settings.get("channel_crops", {}))
+        )
+        update_screensaver_inhibition()
+        update_chat_logging()
+        music_bed.configure(
+            str(current_settings.get("music_bed_path", "")),
+            float(current_settings.get("music_bed_volume", 0.15)),
+        )
+        commentary.configure(
+            str(current_settings.get("commentary_input", "")),
+            float(current_settings.get("commentary_volume", 1.0)),
+        )
+        commentary_stream.set_volume(
+            float(current_settings.get("commentary_volume", 1.0))
+        )
+        update_commentary_channel()
+        player_window.set_duck_volume(
+            float(current_settings.get("duck_volume", 0.25))
+        )
+        save_state(current_streams, current_settings, save_file)
         if (
             manual_columns != previous_manual_columns
             or manual_rows != previous_manual_rows
         ):
+            refresh_active_streams()
+
+    def on_focus_changed(
+        channel: str | None,
+        previous: str | None,
+        reason: str,
+    ) -> None:
+        record_history_event(
+            "focus",
+            history_file=history_file,
+            channel=channel,
+            previous=previous,
+            reason=reason,
+        )
+        publish_event(
+            "focus",
+            channel=channel,
+            previous=previous,
+            reason=reason,
+        )
+
+    def on_subtitles_toggled(enabled: bool) -> None:
+        current_settings["subtitles_enabled"] = enabled
+        save_state(current_streams, current_settings, save_file)
+
+    def on_marker_requested(note: str) -> None:
+        now = time.monotonic()
+        focused_channel = (
+            shown_channels[0] if shown_focused and shown_channels else None
+        )
+        on_screen_sec = {
+            channel: round(now - started, 1)
+            for channel, started in shown_since.items()
+        }
+        record_history_event(
+            "marker",
+            history_file=markers_file,
+            channel=focused_channel,
+            channels=shown_channels,
+            on_screen_sec=on_screen_sec,
+            note=note,
+        )
+        publish_event("marker", channel=focused_channel, note=note)
+        if focused_channel:
+            print(f"Marker recorded for {focused_channel}.")
+        else:
+            print("Marker recorded.")
+
+    def refresh_active_streams() -> None:
+        if sync_follower is None:
             control_panel.force_refresh_active_streams()
+            return
+        # Followers ignore the local panel, so re-apply the leader's last state.
+        state = sync_follower.last_state()
+        if state is not None:
+            channels, focused, manual_layout = state
+            on_active_streams_changed(list(channels), focused, manual_layout)
+
+    def on_stream_error(channel: str, message: str) -> None:
+        publish_event("error", channel=channel, message=message)
+        if args.status_json is not None:
+            # Only print_status drains this list.
+            status_errors.append({"channel": channel, "message": message})
+        channel_key = channel.lower()
+        if channel_key in pending_failovers:
+            return
+        if channel not in player_window.displayed_channels():
+            return
+        current_quality = _channel_quality_overrides(
+            current_settings,
+            quality_fallbacks,
+        ).get(channel_key, int(current_settings.get("max_stream_quality", 720)))
+        lower_steps = [step for step in QUALITY_STEPS if step < current_quality]
+        invalidate_channel(channel, oauth_token)
+        pending_failovers.add(channel_key)
+        channel_failures[channel_key] = channel_failures.get(channel_key, 0) + 1
+        max_failures = int(
+            _channel_reconnect_policies(current_settings)
+            .get(channel_key, {})
+            .get("max_failures", 0)
+        )
+        if max_failures and channel_failures[channel_key] >= max_failures:
+            # Flaky channels are dropped early instead of stepping down.
+            channel_failures.pop(channel_key, None)
+            quality_fallbacks.pop(channel_key, None)
+            mark_channel_failed(channel, oauth_token)
+            log_perf(
+                "app.channel_dropped",
+                channel=channel,
+                failures=max_failures,
+            )
+            publish_event("skipped", channel=channel)
+            player_window.set_reconnecting(channel, 0)
+        elif lower_steps:
+            quality_fallbacks[channel_key] = lower_steps[-1]
+            player_window.set_reconnecting(channel, channel_failures[channel_key])
+            log_perf(
+                "app.rendition_failover",
+                channel=channel,
+                quality=lower_steps[-1],
+            )
+        else:
+            quality_fallbacks.pop(channel_key, None)
+            mark_channel_failed(channel, oauth_token)
+            publish_event("skipped", channel=channel)
+            player_window.set_reconnecting(channel, 0)
+        refresh_active_streams()
 
     def on_overlay_info_changed(
         info: dict[str, dict[str, str | None]],
@@ -172,14 +959,200 @@ def main() -> int:
         player_window.set_overlay_info(info, enabled)
 
     control_panel.manual_streams_changed.connect(on_manual_streams_changed)
-    control_panel.active_streams_changed.connect(on_active_streams_changed)
+    sync_leader: SyncLeader | None = None
+    sync_follower: SyncFollower | None = None
+    if args.sync == "leader":
+        sync_leader = SyncLeader(args.sync_address, args.sync_port, app)
+        sync_leader.publish(initial_streams, False, initial_manual_layout)
+
+        def on_local_streams_changed(
+            updated: list[str],
+            focused: bool,
+            manual_layout: bool,
+        ) -> None:
+            sync_leader.publish(updated, focused, manual_layout)
+            on_active_streams_changed(updated, focused, manual_layout)
+
+        control_panel.active_streams_changed.connect(on_local_streams_changed)
+    elif args.sync == "follower":
+        # The leader decides what is shown; local panel changes are ignored.
+        sync_follower = SyncFollower(args.sync_address, args.sync_port, app)
+        sync_follower.state_received.connect(on_active_streams_changed)
+    else:
+        control_panel.active_streams_changed.connect(on_active_streams_changed)
     control_panel.settings_changed.connect(on_settings_changed)
     control_panel.overlay_info_changed.connect(on_overlay_info_changed)
+    control_panel.focus_changed.connect(on_focus_changed)
+    player_window.stream_error.connect(on_stream_error)
+    player_window.subtitles_toggled.connect(on_subtitles_toggled)
+    player_window.marker_requested.connect(on_marker_requested)
+
+    def on_blanked_changed(blanked: bool) -> None:
+        record_history_event("blank", history_file=history_file, active=blanked)
+        publish_event("blank", active=blanked)
+        update_music_bed()
+
+    player_window.blanked_changed.connect(on_blanked_changed)
+    control_panel.restore_session_state(session_state)
     control_panel.fullscreen_toggled.connect(player_window.set_fullscreen)
     control_panel.show()
 
+    def on_control_command(command: str, argument: str) -> None:
+        if command == "focus":
+            if argument:
+                control_panel.focus_channel(argument)
+        elif command == "unfocus":
+            control_panel.clear_focus()
+        elif command in ("next", "previous"):
+            control_panel.cycle_focus(
+                1 if command == "next" else -1,
+                player_window.displayed_channels(),
+            )
+        elif command in ("pause", "resume"):
+            control_panel.set_pace_autofocus(command == "resume")
+        elif command in ("blank", "unblank"):
+            player_window.set_blanked(command == "blank")
+
+    udp_control: UdpControl | None = None
+    control_port = int(settings.get("control_udp_port", 0))
+    if control_port:
+        control_secret = str(settings.get("control_secret", ""))
+        if control_secret:
+            udp_control = UdpControl(control_port, control_secret, app)
+            udp_control.command_received.connect(on_control_command)
+        else:
+            print("udp control needs control_secret in save.json; not listening")
+
+    pending_ad_checks: dict[str, _AdCheckWorker] = {}
+
+    def on_ad_checked(channel: str, url: str, has_ad: bool) -> None:
+        pending_ad_checks.pop(channel, None)
+        if not bool(current_settings.get("ad_mute_enabled", False)):
+            return
+        player_window.set_ad_break(channel, url, has_ad)
+
+    def check_ad_breaks() -> None:
+        if not bool(current_settings.get("ad_mute_enabled", False)):
+            player_window.clear_ad_breaks()
+            return
+        for stream in player_window.audible_streams():
+            if stream.channel in pending_ad_checks:
+                continue
+            worker = _AdCheckWorker(stream.channel, stream.url)
+            worker.signals.finished.connect(on_ad_checked)
+            pending_ad_checks[stream.channel] = worker
+            thread_pool.start(worker)
+
+    ad_check_timer = QtCore.QTimer()
+    ad_check_timer.setInterval(4_000)
+    ad_check_timer.timeout.connect(check_ad_breaks)
+    ad_check_timer.start()
+
+    def update_music_bed() -> None:
+        # A blank is meant to silence everything, the music bed included.
+        music_bed.set_active(
+            not player_window.is_blanked()
+            and not player_window.has_audible_stream()
+        )
+
+    # Mutes, ad breaks and stream changes all feed into this, so just poll.
+    music_bed_timer = QtCore.QTimer()
+    music_bed_timer.setInterval(1_000)
+    music_bed_timer.timeout.connect(update_music_bed)
+    music_bed_timer.start()
+    update_music_bed()
+
+    metadata_timer = QtCore.QTimer()
+    metadata_timer.setInterval(180_000)
+    metadata_timer.timeout.connect(refresh_metadata)
+    metadata_timer.start()
+    # Resolving only gives title and category; fetch uptime and viewers soon.
+    QtCore.QTimer.singleShot(5_000, refresh_metadata)
+
+    watch_stats_timer = QtCore.QTimer()
+    watch_stats_timer.setInterval(30_000)
+    watch_stats_timer.timeout.connect(watch_tracker.flush)
+    watch_stats_timer.start()
+
+    def stream_status(metadata: StreamMetadata) -> dict[str, object]:
+        uptime_sec = None
+        if metadata.started_at is not None:
+            uptime_sec = round(
+                (datetime.now().astimezone() - metadata.started_at).total_seconds()
+            )
+        return {
+            "title": metadata.title,
+            "category": metadata.category,
+            "uptime_sec": uptime_sec,
+            "viewers": metadata.viewers,
+        }
+
+    def print_status() -> None:
+        upcoming = control_panel.upcoming_lineup_slot()
+        next_switch = None
+        if upcoming is not None:
+            next_switch = {
+                "channel": upcoming.channel,
+                "runner": upcoming.runner,
+                "in_sec": round(
+                    (upcoming.start - datetime.now().astimezone()).total_seconds()
+                ),
+            }
+        status = {
+            "timestamp": datetime.now().astimezone().isoformat(
+                timespec="seconds"
+            ),
+            "displayed": player_window.displayed_channels(),
+            "focus_layout": shown_focused,
+            "focused_channel": control_panel.session_state().get(
+                "focused_channel"
+            ),
+            "audible": [
+                stream.channel for stream in player_window.audible_streams()
+            ],
+            "offline": sorted(offline_channels),
+            "next_switch": next_switch,
+            "errors": list(status_errors),
+            "streams": {
+                channel: stream_status(metadata)
+                for channel, metadata in displayed_metadata(
+                    player_window.displayed_channels()
+                ).items()
+            },
+        }
+        status_errors.clear()
+        # One object per line so wrappers can read stdout line by line.
+        print(json.dumps(status), flush=True)
+
+    status_timer = QtCore.QTimer()
+    if args.status_json is not None:
+        status_timer.setInterval(max(500, int(args.status_json * 1000)))
+        status_timer.timeout.connect(print_status)
+        status_timer.start()
+
     def on_shutdown() -> None:
-        pending_resolve_workers.clear()
+        status_timer.stop()
+        ad_check_timer.stop()
+        pending_ad_checks.clear()
+        music_bed_timer.stop()
+        metadata_timer.stop()
+        music_bed.shutdown()
+        commentary.shutdown()
+        commentary_stream.shutdown()
+        chat_logger.stop()
+        if sync_leader is not None:
+            sync_leader.stop()
+        if sync_follower is not None:
+            sync_follower.stop()
+        if udp_control is not None:
+            udp_control.stop()
+        watch_stats_timer.stop()
+        watch_tracker.flush()
+        screensaver_inhibitor.set_active(False)
+        session = control_panel.session_state()
+        session["muted"] = player_window.channel_mute_states()
+        save_session_state(current_streams, session, session_file)
+        cancel_pending_resolves()
         player_window.shutdown()
         control_panel.shutdown()
 
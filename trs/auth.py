@@ -1,11 +1,61 @@
+import json
 import os
+import sys
+from pathlib import Path
 
-from .config import TOKEN_ENV_VAR
+from .config import CREDENTIALS_FILE_NAME, TOKEN_ENV_VAR
 
 
 def get_oauth_token() -> str:
     token = os.environ.get(TOKEN_ENV_VAR, "").strip()
+    if token:
+        if token != load_cached_token():
+            save_cached_token(token)
+        return token
+    token = load_cached_token()
     if not token:
         print(f"missing required auth token: set {TOKEN_ENV_VAR}")
         raise SystemExit(2)
     return token
+
+
+def credentials_path() -> Path:
+    if sys.platform == "win32":
+        base = os.environ.get("APPDATA") or str(Path.home() / "AppData" / "Roaming")
+    else:
+        base = os.environ.get("XDG_CONFIG_HOME") or str(Path.home() / ".config")
+    return Path(base) / "trs" / CREDENTIALS_FILE_NAME
+
+
+def load_cached_token() -> str:
+    path = credentials_path()
+    try:
+        payload = json.loads(path.read_text(encoding="utf-8"))
+    except FileNotFoundError:
+        return ""
+    except (OSError, ValueError) as exc:
+        print(f"ignoring unreadable credentials file '{path}': {exc}")
+        return ""
+    token = payload.get("oauth_token") if isinstance(payload, dict) else None
+    return str(token).strip() if token else ""
+
+
+def save_cached_token(token: str) -> None:
+    path = credentials_path()
+    try:
+        path.parent.mkdir(parents=True, exist_ok=True)
+        # Create owner-only before writing so the token is never world-readable.
+        fd = os.open(path, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+        with os.fdopen(fd, "w", encoding="utf-8") as handle:
+            json.dump({"oauth_token": token}, handle)
+    except OSError as exc:
+        print(f"could not cache auth token in '{path}': {exc}")
+
+
+def clear_cached_token() -> None:
+    try:
+        credentials_path().unlink()
+    except FileNotFoundError:
+        pass
+    except OSError as exc:
+        print(f"could not remove cached auth token: {exc}")
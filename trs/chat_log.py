@@ -0,0 +1,130 @@
+import random
+from datetime import datetime
+from pathlib import Path
+
+from PySide6 import QtCore, QtNetwork
+
+from .perf_log import log_perf
+
+_IRC_HOST = "irc.chat.twitch.tv"
+_IRC_PORT = 6697
+_RECONNECT_DELAY_MS = 5_000
+
+
+class ChatLogger(QtCore.QObject):
+    def __init__(
+        self,
+        log_dir: Path,
+        parent: QtCore.QObject | None = None,
+    ) -> None:
+        super().__init__(parent)
+        self._log_dir = log_dir
+        self._channels: set[str] = set()
+        self._joined: set[str] = set()
+        self._buffer = b""
+        self._registered = False
+        self._stopping = False
+        self._socket = QtNetwork.QSslSocket(self)
+        self._socket.encrypted.connect(self._on_connected)
+        self._socket.readyRead.connect(self._on_ready_read)
+        self._socket.disconnected.connect(self._on_disconnected)
+        self._socket.errorOccurred.connect(self._on_error)
+        self._reconnect_timer = QtCore.QTimer(self)
+        self._reconnect_timer.setSingleShot(True)
+        self._reconnect_timer.setInterval(_RECONNECT_DELAY_MS)
+        self._reconnect_timer.timeout.connect(self._connect)
+
+    def set_channels(self, channels: list[str]) -> None:
+        self._channels = {
+            channel.strip().lower() for channel in channels if channel.strip()
+        }
+        unconnected = (
+            self._socket.state() == QtNetwork.QAbstractSocket.UnconnectedState
+        )
+        if not self._channels:
+            self._reconnect_timer.stop()
+            if not unconnected:
+                self._socket.disconnectFromHost()
+            return
+        if unconnected:
+            if not self._reconnect_timer.isActive():
+                self._connect()
+            return
+        self._sync_joins()
+
+    def stop(self) -> None:
+        self._stopping = True
+        self._reconnect_timer.stop()
+        self._socket.disconnectFromHost()
+
+    def _connect(self) -> None:
+        if self._stopping or not self._channels:
+            return
+        self._buffer = b""
+        self._registered = False
+        self._joined.clear()
+        self._socket.connectToHostEncrypted(_IRC_HOST, _IRC_PORT)
+
+    def _on_connected(self) -> None:
+        # Anonymous read-only login; Twitch accepts any justinfan nick.
+        nick = f"justinfan{random.randint(10000, 99999)}"
+        self._send(f"NICK {nick}")
+
+    def _on_disconnected(self) -> None:
+        self._registered = False
+        self._joined.clear()
+        if not self._stopping and self._channels:
+            self._reconnect_timer.start()
+
+    def _on_error(self, _error: QtNetwork.QAbstractSocket.SocketError) -> None:
+        log_perf("chat_log.socket_error", error=self._socket.errorString())
+        if (
+            self._socket.state() == QtNetwork.QAbstractSocket.UnconnectedState
+            and not self._stopping
+            and self._channels
+        ):
+            self._reconnect_timer.start()
+
+    def _on_ready_read(self) -> None:
+        self._buffer += bytes(self._socket.readAll())
+        while b"\r\n" in self._buffer:
+            raw_line, self._buffer = self._buffer.split(b"\r\n", 1)
+            self._handle_line(raw_line.decode("utf-8", errors="replace"))
+
+    def _handle_line(self, line: str) -> None:
+        if line.startswith("PING"):
+            self._send("PONG" + line[4:])
+            return
+        prefix, _, rest = line.partition(" ")
+        command, _, params = rest.partition(" ")
+        if command == "001":
+            self._registered = True
+            self._sync_joins()
+        elif command == "PRIVMSG" and prefix.startswith(":"):
+            target, _, message = params.partition(" :")
+            nick = prefix[1:].split("!", 1)[0]
+            self._write_message(target.lstrip("#"), nick, message)
+
+    def _sync_joins(self) -> None:
+        if not self._registered:
+            return
+        for channel in sorted(self._channels - self._joined):
+            self._send(f"JOIN #{channel}")
+        for channel in sorted(self._joined - self._channels):
+            self._send(f"PART #{channel}")
+        self._joined = set(self._channels)
+
+    def _write_message(self, channel: str, nick: str, message: str) -> None:
+        if channel not in self._joined:
+            return
+        timestamp = datetime.now().astimezone().isoformat(timespec="seconds")
+        target = self._log_dir / f"{channel}.log"
+        try:
+            self._log_dir.mkdir(parents=True, exist_ok=True)
+            with target.open("a", encoding="utf-8") as handle:
+                handle.write(f"[{timestamp}] {nick}: {message}\n")
+        except OSError as exc:
+            print(f"chat log write failed for '{channel}': {exc}")
+
+    def _send(self, line: str) -> None:
+        self._socket.write((line + "\r\n").encode("utf-8"))
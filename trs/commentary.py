@@ -0,0 +1,100 @@
+from PySide6 import QtCore, QtMultimedia
+
+from .perf_log import log_perf
+
+
+class CommentaryInput(QtCore.QObject):
+    def __init__(self, parent: QtCore.QObject | None = None) -> None:
+        super().__init__(parent)
+        self._device_name = ""
+        self._session = QtMultimedia.QMediaCaptureSession(self)
+        self._audio_input: QtMultimedia.QAudioInput | None = None
+        self._audio_output = QtMultimedia.QAudioOutput(self)
+        self._session.setAudioOutput(self._audio_output)
+
+    def configure(self, device_name: str, volume: float) -> None:
+        self._audio_output.setVolume(max(0.0, min(1.0, volume)))
+        device_name = device_name.strip()
+        if device_name == self._device_name:
+            return
+        self._device_name = device_name
+        self._release_input()
+        if not device_name:
+            return
+        device = _find_input_device(device_name)
+        if device is None:
+            names = [
+                item.description()
+                for item in QtMultimedia.QMediaDevices.audioInputs()
+            ]
+            print(
+                f"commentary input '{device_name}' not found;"
+                f" available: {', '.join(names) or 'none'}"
+            )
+            return
+        # The capture session plays the input straight to the default output.
+        self._audio_input = QtMultimedia.QAudioInput(device, self)
+        self._session.setAudioInput(self._audio_input)
+        log_perf("commentary.started", device=device.description())
+
+    def shutdown(self) -> None:
+        self._device_name = ""
+        self._release_input()
+
+    def _release_input(self) -> None:
+        if self._audio_input is None:
+            return
+        self._session.setAudioInput(None)
+        self._audio_input.deleteLater()
+        self._audio_input = None
+
+
+class CommentaryStream(QtCore.QObject):
+    failed = QtCore.Signal(str)
+
+    def __init__(self, parent: QtCore.QObject | None = None) -> None:
+        super().__init__(parent)
+        self._url = ""
+        self._player = QtMultimedia.QMediaPlayer(self)
+        self._audio_output = QtMultimedia.QAudioOutput(self)
+        self._player.setAudioOutput(self._audio_output)
+        self._player.errorOccurred.connect(self._on_error)
+
+    def set_volume(self, volume: float) -> None:
+        self._audio_output.setVolume(max(0.0, min(1.0, volume)))
+
+    def set_url(self, url: str) -> None:
+        if url == self._url:
+            return
+        self._url = url
+        self._player.stop()
+        # No video output, so only the stream's audio is played.
+        self._player.setSource(QtCore.QUrl(url) if url else QtCore.QUrl())
+        if url:
+            self._player.play()
+            log_perf("commentary.stream_started")
+
+    def shutdown(self) -> None:
+        self.set_url("")
+
+    def _on_error(
+        self,
+        error: QtMultimedia.QMediaPlayer.Error,
+        message: str,
+    ) -> None:
+        if error == QtMultimedia.QMediaPlayer.NoError or not self._url:
+            return
+        print(f"commentary stream failed: {message}")
+        # Forget the URL so the re-resolved one restarts playback.
+        self._url = ""
+        self.failed.emit(message)
+
+
+def _find_input_device(name: str) -> QtMultimedia.QAudioDevice | None:
+    if name.lower() == "default":
+        device = QtMultimedia.QMediaDevices.defaultAudioInput()
+        return None if device.isNull() else device
+    for device in QtMultimedia.QMediaDevices.audioInputs():
+        if device.description().lower() == name.lower():
+            return device
+    return None
@@ -1,9 +1,25 @@
 from pathlib import Path
 
 TOKEN_ENV_VAR = "TWITCH_OAUTH_TOKEN"
+CREDENTIALS_FILE_NAME = "credentials.json"
 DEFAULT_SAVE_FILE = Path("save.json")
 PERF_LOG_FILE = Path("perf.log")
+HISTORY_FILE = Path("history.jsonl")
+MARKERS_FILE = Path("markers.jsonl")
+WATCH_STATS_FILE = Path("watch_stats.json")
+SESSION_FILE = Path("session.json")
 APP_TITLE = "TRS"
 CONTROL_PANEL_TITLE = "TRS Control Panel"
 PACE_AUTOFOCUS_THRESHOLD = 0.6
 PACE_PACEMAN_THRESHOLD = 0.8
+QUALITY_STEPS = [160, 360, 480, 720, 1080]
+DEFAULT_KEYBINDINGS = {
+    "toggle_fullscreen": "F11",
+    "exit_fullscreen": "Esc",
+    "cycle_audio_track": "A",
+    "toggle_subtitles": "C",
+    "add_marker": "M",
+    "add_marker_with_note": "Shift+M",
+    "push_to_duck": "D",
+    "toggle_blank": "Ctrl+B",
+}
@@ -0,0 +1,47 @@
+from dataclasses import dataclass
+
+from .stream_resolver import get_stream_metadata
+
+
+@dataclass(frozen=True)
+class ContentFilter:
+    blocked_channels: frozenset[str] = frozenset()
+    blocked_title_keywords: tuple[str, ...] = ()
+    blocked_categories: frozenset[str] = frozenset()
+
+    @classmethod
+    def from_settings(cls, settings: dict[str, object]) -> "ContentFilter":
+        return cls(
+            blocked_channels=frozenset(
+                _lowered(settings.get("channel_blocklist", []))
+            ),
+            blocked_title_keywords=tuple(
+                _lowered(settings.get("blocked_title_keywords", []))
+            ),
+            blocked_categories=frozenset(
+                _lowered(settings.get("blocked_categories", []))
+            ),
+        )
+
+    def allows(self, channel: str) -> bool:
+        channel = channel.strip().lower()
+        if channel in self.blocked_channels:
+            return False
+        if not self.blocked_title_keywords and not self.blocked_categories:
+            return True
+        # Title and category are only known once the stream has been resolved.
+        metadata = get_stream_metadata(channel)
+        if metadata is None:
+            return True
+        if metadata.category and (
+            metadata.category.strip().lower() in self.blocked_categories
+        ):
+            return False
+        title = (metadata.title or "").lower()
+        return not any(keyword in title for keyword in self.blocked_title_keywords)
+
+
+def _lowered(values: object) -> list[str]:
+    if not isinstance(values, list):
+        return []
+    return [str(value).strip().lower() for value in values if str(value).strip()]
@@ -0,0 +1,30 @@
+import json
+from datetime import datetime
+from pathlib import Path
+
+from .config import HISTORY_FILE
+
+
+def history_entry(event: str, **fields: object) -> dict[str, object]:
+    entry: dict[str, object] = {
+        "timestamp": datetime.now().astimezone().isoformat(
+            timespec="milliseconds"
+        ),
+        "event": event,
+    }
+    entry.update(fields)
+    return entry
+
+
+def record_history_event(
+    event: str,
+    history_file: Path | None = None,
+    **fields: object,
+) -> None:
+    target = history_file or HISTORY_FILE
+    entry = history_entry(event, **fields)
+    try:
+        with target.open("a", encoding="utf-8") as handle:
+            handle.write(json.dumps(entry) + "\n")
+    except OSError as exc:
+        print(f"history write failed: {exc}")
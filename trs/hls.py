@@ -0,0 +1,146 @@
+import re
+import urllib.parse
+import urllib.request
+from dataclasses import dataclass
+
+_ATTRIBUTE_RE = re.compile(r'([A-Z0-9-]+)=("[^"]*"|[^,]*)')
+
+
+@dataclass(frozen=True)
+class Rendition:
+    url: str
+    bandwidth: int | None
+    width: int | None
+    height: int | None
+    frame_rate: float | None
+    name: str | None
+
+
+def fetch_playlist(url: str, timeout: float = 8.0) -> str:
+    request = urllib.request.Request(url, headers={"User-Agent": "trs"})
+    with urllib.request.urlopen(request, timeout=timeout) as response:
+        return response.read().decode("utf-8", errors="replace")
+
+
+def parse_attributes(line: str) -> dict[str, str]:
+    _, _, attribute_list = line.partition(":")
+    attributes: dict[str, str] = {}
+    for key, value in _ATTRIBUTE_RE.findall(attribute_list):
+        attributes[key] = value.strip('"')
+    return attributes
+
+
+def parse_master_playlist(text: str, base_url: str = "") -> list[Rendition]:
+    media_names: dict[str, str] = {}
+    renditions: list[Rendition] = []
+    pending: dict[str, str] | None = None
+    for raw_line in text.splitlines():
+        line = raw_line.strip()
+        if not line:
+            continue
+        if line.startswith("#EXT-X-MEDIA:"):
+            attributes = parse_attributes(line)
+            if attributes.get("TYPE") == "VIDEO" and "GROUP-ID" in attributes:
+                media_names[attributes["GROUP-ID"]] = attributes.get("NAME", "")
+            continue
+        if line.startswith("#EXT-X-STREAM-INF:"):
+            pending = parse_attributes(line)
+            continue
+        if line.startswith("#") or pending is None:
+            continue
+        width, height = _parse_resolution(pending.get("RESOLUTION"))
+        group_id = pending.get("VIDEO", "")
+        renditions.append(
+            Rendition(
+                url=urllib.parse.urljoin(base_url, line),
+                bandwidth=_parse_int(pending.get("BANDWIDTH")),
+                width=width,
+                height=height,
+                frame_rate=_parse_float(pending.get("FRAME-RATE")),
+                name=media_names.get(group_id) or group_id or None,
+            )
+        )
+        pending = None
+    return renditions
+
+
+def media_playlist_has_ad(text: str) -> bool:
+    for raw_line in text.splitlines():
+        line = raw_line.strip()
+        if line.startswith("#EXT-X-DATERANGE:"):
+            attributes = parse_attributes(line)
+            if attributes.get("CLASS") == "twitch-stitched-ad":
+                return True
+            if attributes.get("ID", "").startswith("stitched-ad"):
+                return True
+        elif line.startswith("#EXTINF:"):
+            _, _, title = line.partition(",")
+            # Twitch titles regular segments "live"; ad segments get a vendor name.
+            if title and title != "live":
+                return True
+    return False
+
+
+def select_rendition(
+    renditions: list[Rendition],
+    max_height: int | None = None,
+    max_bandwidth_kbps: int | None = None,
+) -> Rendition | None:
+    candidates: list[Rendition] = []
+    for rendition in renditions:
+        if rendition.height is None:
+            continue
+        if max_height is not None and rendition.height > max_height:
+            continue
+        if (
+            max_bandwidth_kbps
+            and rendition.bandwidth is not None
+            and rendition.bandwidth > max_bandwidth_kbps * 1000
+        ):
+            continue
+        candidates.append(rendition)
+    if not candidates:
+        return None
+    return max(
+        candidates,
+        key=lambda rendition: (
+            rendition.height or 0,
+            rendition.frame_rate or 0.0,
+            rendition.bandwidth or 0,
+        ),
+    )
+
+
+def lowest_video_rendition(renditions: list[Rendition]) -> Rendition | None:
+    candidates = [rendition for rendition in renditions if rendition.height]
+    if not candidates:
+        return None
+    return min(
+        candidates,
+        key=lambda rendition: (rendition.bandwidth or 0, rendition.height or 0),
+    )
+
+
+def _parse_resolution(value: str | None) -> tuple[int | None, int | None]:
+    if not value or "x" not in value:
+        return None, None
+    width, _, height = value.partition("x")
+    return _parse_int(width), _parse_int(height)
+
+
+def _parse_int(value: str | None) -> int | None:
+    if value is None:
+        return None
+    try:
+        return int(value)
+    except ValueError:
+        return None
+
+
+def _parse_float(value: str | None) -> float | None:
+    if value is None:
+        return None
+    try:
+        return float(value)
+    except ValueError:
+        return None
@@ -0,0 +1,82 @@
+import importlib.util
+from collections.abc import Callable
+from pathlib import Path
+from types import ModuleType
+
+from .perf_log import log_perf
+
+
+class HookController:
+    # Exposed to hook scripts as the module global `trs`.
+    def __init__(
+        self,
+        focus: Callable[[str], None],
+        clear_focus: Callable[[], None],
+        displayed: Callable[[], list[str]],
+        metadata: Callable[[str], object],
+    ) -> None:
+        self._focus = focus
+        self._clear_focus = clear_focus
+        self._displayed = displayed
+        self._metadata = metadata
+
+    def focus(self, channel: str) -> None:
+        if channel and channel.strip():
+            self._focus(channel.strip())
+
+    def clear_focus(self) -> None:
+        self._clear_focus()
+
+    def displayed(self) -> list[str]:
+        return list(self._displayed())
+
+    def metadata(self, channel: str) -> object:
+        return self._metadata(channel)
+
+
+class ScriptHooks:
+    def __init__(
+        self,
+        script_path: str = "",
+        controller: HookController | None = None,
+    ) -> None:
+        self._module: ModuleType | None = None
+        if script_path:
+            self._module = _load_script(Path(script_path), controller)
+
+    def call(self, event: str, **fields: object) -> None:
+        if self._module is None:
+            return
+        handler = getattr(self._module, f"on_{event}", None)
+        if not callable(handler):
+            return
+        try:
+            handler(**fields)
+        except Exception as exc:
+            log_perf(
+                "hooks.handler_failed",
+                hook_event=event,
+                error=type(exc).__name__,
+            )
+            print(f"hook 'on_{event}' failed: {exc}")
+
+
+def _load_script(
+    path: Path,
+    controller: HookController | None,
+) -> ModuleType | None:
+    if not path.is_file():
+        print(f"hooks script not found: {path}")
+        return None
+    spec = importlib.util.spec_from_file_location("trs_user_hooks", path)
+    if spec is None or spec.loader is None:
+        print(f"hooks script could not be loaded: {path}")
+        return None
+    module = importlib.util.module_from_spec(spec)
+    module.trs = controller
+    try:
+        spec.loader.exec_module(module)
+    except Exception as exc:
+        print(f"hooks script '{path}' failed to load: {exc}")
+        return None
+    return module
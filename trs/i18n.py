@@ -0,0 +1,40 @@
+import json
+from pathlib import Path
+
+DEFAULT_LOCALE = "en"
+_LOCALE_DIR = Path(__file__).resolve().parent / "locales"
+_STRINGS: dict[str, str] = {}
+
+
+def set_locale(locale: str) -> None:
+    # English is loaded first so a partial translation falls back per key.
+    strings = _load_strings(_LOCALE_DIR / f"{DEFAULT_LOCALE}.json")
+    locale = locale.strip()
+    if locale and locale != DEFAULT_LOCALE:
+        custom = Path(locale).expanduser()
+        target = custom if custom.suffix == ".json" else _LOCALE_DIR / f"{locale}.json"
+        strings.update(_load_strings(target))
+    _STRINGS.clear()
+    _STRINGS.update(strings)
+
+
+def tr(key: str, **fields: object) -> str:
+    if not _STRINGS:
+        set_locale(DEFAULT_LOCALE)
+    template = _STRINGS.get(key, key)
+    try:
+        return template.format(**fields)
+    except (KeyError, IndexError, ValueError):
+        print(f"bad placeholder in locale string '{key}': {template}")
+        return template
+
+
+def _load_strings(path: Path) -> dict[str, str]:
+    try:
+        payload = json.loads(path.read_text(encoding="utf-8"))
+    except (OSError, ValueError) as exc:
+        print(f"could not load locale file '{path}': {exc}")
+        return {}
+    if not isinstance(payload, dict):
+        return {}
+    return {str(key): str(value) for key, value in payload.items()}
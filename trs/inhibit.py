@@ -0,0 +1,95 @@
+import subprocess
+import sys
+
+from .config import APP_TITLE
+from .perf_log import log_perf
+
+_ES_CONTINUOUS = 0x80000000
+_ES_SYSTEM_REQUIRED = 0x00000001
+_ES_DISPLAY_REQUIRED = 0x00000002
+
+
+class ScreenSaverInhibitor:
+    def __init__(self) -> None:
+        self._active = False
+        self._dbus_cookie: int | None = None
+        self._caffeinate: subprocess.Popen | None = None
+
+    @property
+    def active(self) -> bool:
+        return self._active
+
+    def set_active(self, active: bool) -> None:
+        if active == self._active:
+            return
+        try:
+            if active:
+                self._inhibit()
+            else:
+                self._release()
+        except Exception as exc:
+            log_perf(
+                "inhibit.failed",
+                active=active,
+                error=type(exc).__name__,
+            )
+            return
+        self._active = active
+        log_perf("inhibit.changed", active=active)
+
+    def _inhibit(self) -> None:
+        if sys.platform == "win32":
+            import ctypes
+
+            ctypes.windll.kernel32.SetThreadExecutionState(
+                _ES_CONTINUOUS | _ES_SYSTEM_REQUIRED | _ES_DISPLAY_REQUIRED
+            )
+        elif sys.platform == "darwin":
+            self._caffeinate = subprocess.Popen(["caffeinate", "-d", "-i"])
+        else:
+            self._dbus_cookie = _dbus_screensaver_call(
+                "Inhibit", APP_TITLE, "Playing streams"
+            )
+
+    def _release(self) -> None:
+        if sys.platform == "win32":
+            import ctypes
+
+            ctypes.windll.kernel32.SetThreadExecutionState(_ES_CONTINUOUS)
+        elif sys.platform == "darwin":
+            if self._caffeinate is not None:
+                self._caffeinate.terminate()
+                self._caffeinate = None
+        elif self._dbus_cookie is not None:
+            # Raises on an error reply, so a failed release keeps the cookie.
+            _dbus_screensaver_call("UnInhibit", _uint32_argument(self._dbus_cookie))
+            self._dbus_cookie = None
+
+
+def _uint32_argument(value: int) -> object:
+    from PySide6 import QtCore, QtDBus
+
+    # A bare Python int is sent as a signed int; UnInhibit takes a "u".
+    argument = QtDBus.QDBusArgument()
+    argument.add(value, QtCore.QMetaType.Type.UInt.value)
+    return argument
+
+
+def _dbus_screensaver_call(method: str, *args: object) -> int | None:
+    from PySide6 import QtDBus
+
+    interface = QtDBus.QDBusInterface(
+        "org.freedesktop.ScreenSaver",
+        "/org/freedesktop/ScreenSaver",
+        "org.freedesktop.ScreenSaver",
+        QtDBus.QDBusConnection.sessionBus(),
+    )
+    if not interface.isValid():
+        raise RuntimeError("org.freedesktop.ScreenSaver is not available")
+    reply = interface.call(method, *args)
+    if reply.type() == QtDBus.QDBusMessage.ErrorMessage:
+        raise RuntimeError(reply.errorMessage())
+    values = reply.arguments()
+    if values:
+        return int(values[0])
+    return None
@@ -0,0 +1,87 @@
+import csv
+import json
+from dataclasses import dataclass
+from datetime import datetime, timedelta
+from pathlib import Path
+
+
+@dataclass(frozen=True)
+class LineupSlot:
+    runner: str
+    channel: str
+    start: datetime
+    estimate: timedelta
+
+    @property
+    def end(self) -> datetime:
+        return self.start + self.estimate
+
+
+def load_lineup(path: Path) -> list[LineupSlot]:
+    text = path.read_text(encoding="utf-8")
+    if path.suffix.lower() == ".json":
+        payload = json.loads(text)
+        if isinstance(payload, dict):
+            payload = payload.get("lineup", [])
+        rows = payload if isinstance(payload, list) else []
+    else:
+        rows = list(csv.DictReader(text.splitlines()))
+    slots: list[LineupSlot] = []
+    for index, row in enumerate(rows, start=1):
+        if not isinstance(row, dict):
+            continue
+        try:
+            slots.append(_parse_slot(row))
+        except (KeyError, TypeError, ValueError) as exc:
+            print(f"skipping lineup entry {index} in '{path}': {exc}")
+    return sorted(slots, key=lambda slot: slot.start)
+
+
+def active_lineup_slots(
+    slots: list[LineupSlot],
+    now: datetime | None = None,
+) -> list[LineupSlot]:
+    current = now or datetime.now().astimezone()
+    return [slot for slot in slots if slot.start <= current < slot.end]
+
+
+def next_lineup_slot(
+    slots: list[LineupSlot],
+    now: datetime | None = None,
+) -> LineupSlot | None:
+    current = now or datetime.now().astimezone()
+    for slot in slots:
+        if slot.start > current:
+            return slot
+    return None
+
+
+def _parse_slot(row: dict) -> LineupSlot:
+    channel = str(row["channel"]).strip()
+    if not channel:
+        raise ValueError("missing channel")
+    runner = str(row.get("runner") or channel).strip()
+    start = datetime.fromisoformat(str(row["start"]).strip())
+    if start.tzinfo is None:
+        start = start.astimezone()
+    return LineupSlot(
+        runner=runner,
+        channel=channel,
+        start=start,
+        estimate=_parse_estimate(row["estimate"]),
+    )
+
+
+def _parse_estimate(value: object) -> timedelta:
+    if isinstance(value, (int, float)):
+        seconds = float(value)
+    else:
+        parts = str(value).strip().split(":")
+        if len(parts) > 3:
+            raise ValueError(f"invalid estimate '{value}'")
+        seconds = 0.0
+        for part in parts:
+            seconds = seconds * 60 + float(part)
+    if seconds <= 0:
+        raise ValueError(f"invalid estimate '{value}'")
+    return timedelta(seconds=seconds)
@@ -0,0 +1,131 @@
+import json
+import re
+import urllib.error
+import urllib.parse
+import urllib.request
+from dataclasses import dataclass
+
+MCSR_RANKED_API_URL = "https://api.mcsrranked.com"
+RANKED_PREFIX = "ranked:"
+_MATCH_PREFIX = "match:"
+LIVE_PREFIX = "live:"
+_TWITCH_URL_PATTERN = re.compile(r"twitch\.tv/([A-Za-z0-9_]+)", re.IGNORECASE)
+
+
+class RankedPlayerNotFound(LookupError):
+    pass
+
+
+@dataclass(frozen=True)
+class RankedLiveMatch:
+    channels: list[str]
+    # Latest timeline event type per channel, e.g. "projectelo.timeline.reset".
+    events: dict[str, str]
+
+
+def resolve_ranked_channels(query: str, timeout: float = 8.0) -> list[str]:
+    query = query.strip()
+    if query.lower().startswith(_MATCH_PREFIX):
+        names = fetch_match_player_names(query[len(_MATCH_PREFIX):], timeout)
+    else:
+        names = [name for name in re.split(r"[\s,]+", query) if name]
+    channels: list[str] = []
+    for name in names:
+        try:
+            channel = fetch_player_twitch_channel(name, timeout)
+        except RankedPlayerNotFound:
+            print(f"ranked player '{name}' not found")
+            continue
+        except Exception as exc:
+            print(f"ranked lookup failed for '{name}': {exc}")
+            continue
+        if channel is None:
+            print(f"ranked player '{name}' has no linked Twitch channel")
+            continue
+        if channel not in channels:
+            channels.append(channel)
+    return channels
+
+
+def fetch_match_player_names(match_id: str, timeout: float = 8.0) -> list[str]:
+    path = f"/matches/{urllib.parse.quote(match_id.strip())}"
+    data = _fetch_data(path, timeout)
+    players = data.get("players") if isinstance(data, dict) else None
+    if not isinstance(players, list):
+        return []
+    return [
+        str(player["nickname"])
+        for player in players
+        if isinstance(player, dict) and player.get("nickname")
+    ]
+
+
+def fetch_player_twitch_channel(name: str, timeout: float = 8.0) -> str | None:
+    path = f"/users/{urllib.parse.quote(name.strip())}"
+    data = _fetch_data(path, timeout)
+    if data is None:
+        raise RankedPlayerNotFound(name)
+    connections = data.get("connections") if isinstance(data, dict) else None
+    twitch = connections.get("twitch") if isinstance(connections, dict) else None
+    if not isinstance(twitch, dict):
+        return None
+    channel = twitch.get("name")
+    return str(channel).strip().lower() if channel else None
+
+
+def fetch_live_match(
+    names: list[str], timeout: float = 8.0
+) -> RankedLiveMatch | None:
+    wanted = {name.strip().lower() for name in names if name.strip()}
+    data = _fetch_data("/live", timeout)
+    matches = data.get("liveMatches") if isinstance(data, dict) else None
+    for match in matches if isinstance(matches, list) else []:
+        if not isinstance(match, dict):
+            continue
+        players = match.get("players")
+        players = players if isinstance(players, list) else []
+        nicknames = {
+            str(player.get("nickname", "")).lower()
+            for player in players
+            if isinstance(player, dict)
+        }
+        if not wanted & nicknames:
+            continue
+        streams = match.get("data")
+        streams = streams if isinstance(streams, dict) else {}
+        channels: list[str] = []
+        events: dict[str, str] = {}
+        for player in players:
+            if not isinstance(player, dict):
+                continue
+            stream = streams.get(player.get("uuid"))
+            if not isinstance(stream, dict):
+                continue
+            found = _TWITCH_URL_PATTERN.search(str(stream.get("liveUrl") or ""))
+            if found is None:
+                continue
+            channel = found.group(1).lower()
+            channels.append(channel)
+            timeline = stream.get("timeline")
+            if isinstance(timeline, dict) and timeline.get("type"):
+                events[channel] = str(timeline["type"])
+        return RankedLiveMatch(channels=channels, events=events)
+    return None
+
+
+def _fetch_data(path: str, timeout: float) -> object:
+    request = urllib.request.Request(
+        f"{MCSR_RANKED_API_URL}{path}",
+        headers={"User-Agent": "trs"},
+    )
+    try:
+        with urllib.request.urlopen(request, timeout=timeout) as response:
+            payload = json.loads(response.read().decode("utf-8"))
+    except urllib.error.HTTPError as exc:
+        if exc.code not in (400, 404):
+            raise
+        # Unknown players and matches come back as an error payload.
+        return None
+    if not isinstance(payload, dict) or payload.get("status") != "success":
+        return None
+    return payload.get("data")
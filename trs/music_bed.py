@@ -0,0 +1,91 @@
+from pathlib import Path
+
+from PySide6 import QtCore, QtMultimedia
+
+from .perf_log import log_perf
+
+_AUDIO_SUFFIXES = {".mp3", ".ogg", ".opus", ".flac", ".wav", ".m4a", ".aac"}
+
+
+class MusicBed(QtCore.QObject):
+    def __init__(self, parent: QtCore.QObject | None = None) -> None:
+        super().__init__(parent)
+        self._tracks: list[Path] = []
+        self._index = 0
+        self._failures = 0
+        self._active = False
+        self._audio_output = QtMultimedia.QAudioOutput(self)
+        self._player = QtMultimedia.QMediaPlayer(self)
+        self._player.setAudioOutput(self._audio_output)
+        self._player.mediaStatusChanged.connect(self._on_media_status)
+
+    def configure(self, path: str, volume: float) -> None:
+        tracks = _collect_tracks(path)
+        self._audio_output.setVolume(max(0.0, min(1.0, volume)))
+        if tracks == self._tracks:
+            return
+        self._tracks = tracks
+        self._index = 0
+        self._failures = 0
+        self._player.stop()
+        if self._active and self._tracks:
+            self._play_current()
+
+    def set_active(self, active: bool) -> None:
+        active = active and bool(self._tracks)
+        if active == self._active:
+            return
+        self._active = active
+        log_perf("music_bed.changed", active=active, tracks=len(self._tracks))
+        if not active:
+            self._player.pause()
+            return
+        if self._player.playbackState() == QtMultimedia.QMediaPlayer.PausedState:
+            self._player.play()
+        else:
+            self._play_current()
+
+    def shutdown(self) -> None:
+        self._active = False
+        self._player.stop()
+
+    def _play_current(self) -> None:
+        track = self._tracks[self._index % len(self._tracks)]
+        self._player.setSource(QtCore.QUrl.fromLocalFile(str(track)))
+        self._player.play()
+
+    def _on_media_status(
+        self,
+        status: QtMultimedia.QMediaPlayer.MediaStatus,
+    ) -> None:
+        if status == QtMultimedia.QMediaPlayer.LoadedMedia:
+            self._failures = 0
+            return
+        if status == QtMultimedia.QMediaPlayer.InvalidMedia:
+            source = self._player.source().toLocalFile()
+            print(f"music bed could not play '{source}'")
+            self._failures += 1
+        elif status != QtMultimedia.QMediaPlayer.EndOfMedia:
+            return
+        if not self._active or not self._tracks:
+            return
+        if self._failures >= len(self._tracks):
+            # Every track failed to load; don't spin through them forever.
+            return
+        self._index = (self._index + 1) % len(self._tracks)
+        self._play_current()
+
+
+def _collect_tracks(path: str) -> list[Path]:
+    if not path:
+        return []
+    target = Path(path).expanduser()
+    if target.is_file():
+        return [target]
+    if not target.is_dir():
+        return []
+    return sorted(
+        item
+        for item in target.iterdir()
+        if item.is_file() and item.suffix.lower() in _AUDIO_SUFFIXES
+    )
@@ -3,6 +3,8 @@ from pathlib import Path
 
 from PySide6 import QtCore
 
+MEDIA_BACKENDS = ("auto", "ffmpeg", "gstreamer", "windows", "darwin")
+
 
 def configure_qt_plugins() -> None:
     # Ensure Qt can find multimedia plugins when running from a bundled Python.
@@ -17,3 +19,9 @@ def configure_qt_plugins() -> None:
         if hasattr(os, "add_dll_directory"):
             os.add_dll_directory(str(pyside_dir))
             os.add_dll_directory(str(plugin_dir))
+
+
+def configure_media_backend(backend: str) -> None:
+    # Qt reads this once, when the first multimedia object is created.
+    if backend and backend != "auto":
+        os.environ["QT_MEDIA_BACKEND"] = backend
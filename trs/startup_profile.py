@@ -0,0 +1,83 @@
+import time
+
+from .perf_log import log_perf
+
+CHANNEL_PHASES = ("resolved", "player_created", "media_loaded", "first_frame")
+
+
+class StartupProfiler:
+    def __init__(self) -> None:
+        self._start = time.perf_counter()
+        self._phases: dict[str, float] = {}
+        self._channels: dict[str, dict[str, float]] = {}
+        self._resolve_ms: dict[str, float] = {}
+        self._expected: set[str] = set()
+        self._reported = False
+
+    @property
+    def reported(self) -> bool:
+        return self._reported
+
+    def mark(self, phase: str) -> None:
+        self._phases.setdefault(phase, self._elapsed_ms())
+
+    def set_resolve_times(self, timings: dict[str, float]) -> None:
+        self._resolve_ms = dict(timings)
+
+    def expect_channels(self, channels: list[str]) -> None:
+        self._expected = set(channels)
+        resolved_at = self._elapsed_ms()
+        for channel in channels:
+            self._channels.setdefault(channel, {})["resolved"] = resolved_at
+
+    def mark_channel(self, channel: str, phase: str) -> None:
+        if self._reported or channel not in self._expected:
+            return
+        self._channels.setdefault(channel, {}).setdefault(
+            phase,
+            self._elapsed_ms(),
+        )
+
+    def complete(self) -> bool:
+        return all(
+            "first_frame" in self._channels.get(channel, {})
+            for channel in self._expected
+        )
+
+    def report(self) -> str:
+        self._reported = True
+        lines = ["Startup profile (ms since launch):"]
+        for phase, elapsed in self._phases.items():
+            lines.append(f"  {phase:<24}{elapsed:>10.0f}")
+            log_perf("startup.phase", duration_ms=elapsed, phase=phase)
+        if self._channels:
+            width = max(len("Channel"), *(len(name) for name in self._channels))
+            header = "".join(f"{phase:>16}" for phase in CHANNEL_PHASES)
+            lines.append(f"  {'Channel':<{width}}{'resolve':>10}{header}")
+            for channel, marks in self._channels.items():
+                resolve_ms = self._resolve_ms.get(channel)
+                cells = _cell(resolve_ms, 10) + "".join(
+                    _cell(marks.get(phase), 16) for phase in CHANNEL_PHASES
+                )
+                lines.append(f"  {channel:<{width}}{cells}")
+                log_perf(
+                    "startup.channel",
+                    channel=channel,
+                    resolve_ms=_format_ms(resolve_ms),
+                    **{
+                        phase: _format_ms(marks.get(phase))
+                        for phase in CHANNEL_PHASES
+                    },
+                )
+        return "\n".join(lines)
+
+    def _elapsed_ms(self) -> float:
+        return (time.perf_counter() - self._start) * 1000.0
+
+
+def _cell(value: float | None, width: int) -> str:
+    return f"{_format_ms(value):>{width}}"
+
+
+def _format_ms(value: float | None) -> str:
+    return "-" if value is None else f"{value:.0f}"
@@ -1,7 +1,12 @@
 import json
+import re
 from pathlib import Path
 
-from .config import DEFAULT_SAVE_FILE
+from .config import DEFAULT_SAVE_FILE, SESSION_FILE
+
+# "channel@480p", "channel@720p60" or "channel@source" in the stream list.
+_QUALITY_SUFFIX = re.compile(r"^(\d{3,4})p?(?:\d{2})?$", re.IGNORECASE)
+_UNCAPPED_QUALITIES = {"source", "best"}
 
 _DEFAULT_SETTINGS: dict[str, object] = {
     "paceman_mode": False,
@@ -13,12 +18,48 @@ _DEFAULT_SETTINGS: dict[str, object] = {
     "manual_grid_rows": 0,
     "overlay_enabled": True,
     "focus_bell_enabled": False,
+    "ad_mute_enabled": False,
+    "inhibit_screensaver": True,
+    "show_offline_placeholders": False,
+    "fair_grid_fill": False,
+    "chat_log_enabled": False,
     "pace_sort_enabled": True,
     "pace_autofocus_enabled": True,
     "pace_autofocus_threshold": 0.6,
     "pace_paceman_enabled": False,
     "pace_paceman_threshold": 0.8,
     "max_stream_quality": 720,
+    "max_stream_bandwidth_kbps": 0,
+    "resolve_retry_attempts": 3,
+    "resolve_retry_backoff_sec": 1.0,
+    "resolve_timeout_sec": 20.0,
+    "player_startup_timeout_sec": 20.0,
+    "video_delay_ms": 0,
+    "channel_video_delays_ms": {},
+    "webhook_urls": [],
+    "hooks_script": "",
+    "preferred_audio_track": "",
+    "subtitles_enabled": False,
+    "subtitle_font_size": 20,
+    "music_bed_path": "",
+    "chat_log_dir": "chat_logs",
+    "lineup_file": "",
+    "music_bed_volume": 0.15,
+    "duck_volume": 0.25,
+    "commentary_volume": 1.0,
+    "commentary_input": "",
+    "commentary_channel": "",
+    "overlay_theme": {},
+    "keybindings": {},
+    "channel_reconnect_policies": {},
+    "channel_crops": {},
+    "channel_max_quality": {},
+    "control_udp_port": 0,
+    "control_secret": "",
+    "locale": "en",
+    "channel_blocklist": [],
+    "blocked_title_keywords": [],
+    "blocked_categories": [],
     "pace_good_splits": {
         "NETHER": 90,
         "S1": 120,
@@ -46,18 +87,70 @@ _BOOL_KEYS = {
     "paceman_hide_offline",
     "overlay_enabled",
     "focus_bell_enabled",
+    "ad_mute_enabled",
+    "inhibit_screensaver",
+    "show_offline_placeholders",
+    "fair_grid_fill",
+    "chat_log_enabled",
     "pace_sort_enabled",
     "pace_autofocus_enabled",
     "pace_paceman_enabled",
+    "subtitles_enabled",
 }
 
-_FLOAT_KEYS = {"pace_autofocus_threshold", "pace_paceman_threshold"}
+_FLOAT_KEYS = {
+    "pace_autofocus_threshold",
+    "pace_paceman_threshold",
+    "resolve_retry_backoff_sec",
+    "resolve_timeout_sec",
+    "player_startup_timeout_sec",
+    "music_bed_volume",
+    "duck_volume",
+    "commentary_volume",
+}
 
-_INT_KEYS = {"max_stream_quality", "manual_grid_columns", "manual_grid_rows"}
+_INT_KEYS = {
+    "max_stream_quality",
+    "max_stream_bandwidth_kbps",
+    "manual_grid_columns",
+    "manual_grid_rows",
+    "resolve_retry_attempts",
+    "subtitle_font_size",
+    "video_delay_ms",
+    "control_udp_port",
+}
 
 _DICT_FLOAT_KEYS = {"pace_good_splits", "pace_progression_bonus"}
 
-_STRING_KEYS = {"paceman_event"}
+_STRING_KEYS = {
+    "paceman_event",
+    "hooks_script",
+    "preferred_audio_track",
+    "music_bed_path",
+    "chat_log_dir",
+    "commentary_input",
+    "commentary_channel",
+    "control_secret",
+    "locale",
+    "lineup_file",
+}
+
+_CHANNEL_INT_KEYS = {"channel_video_delays_ms", "channel_max_quality"}
+
+_STRING_LIST_KEYS = {
+    "webhook_urls",
+    "channel_blocklist",
+    "blocked_title_keywords",
+    "blocked_categories",
+}
+
+# Free-form objects validated by their consumer (e.g. OverlayTheme.from_dict).
+_OBJECT_KEYS = {
+    "overlay_theme",
+    "keybindings",
+    "channel_reconnect_policies",
+    "channel_crops",
+}
 
 
 def _normalize_settings(settings: dict) -> dict[str, object]:
@@ -97,16 +190,46 @@ def _normalize_settings(settings: dict) -> dict[str, object]:
             except (TypeError, ValueError):
                 merged[str(split_key)] = float(default_val)
         normalized[key] = merged
+    for key in _CHANNEL_INT_KEYS:
+        value = settings.get(key, {})
+        if not isinstance(value, dict):
+            normalized[key] = {}
+            continue
+        channel_values: dict[str, int] = {}
+        for channel, candidate in value.items():
+            channel_key = str(channel).strip().lower()
+            if not channel_key:
+                continue
+            try:
+                channel_values[channel_key] = max(0, int(candidate))
+            except (TypeError, ValueError):
+                continue
+        normalized[key] = channel_values
+    for key in _STRING_LIST_KEYS:
+        value = settings.get(key, [])
+        if isinstance(value, str):
+            value = [value]
+        if not isinstance(value, list):
+            normalized[key] = []
+            continue
+        normalized[key] = [
+            str(item).strip() for item in value if str(item).strip()
+        ]
+    for key in _OBJECT_KEYS:
+        value = settings.get(key, {})
+        normalized[key] = dict(value) if isinstance(value, dict) else {}
     return normalized
 
 
 def load_saved_state(
     save_file: Path | None = None,
+    write_back: bool = True,
 ) -> tuple[list[str], dict[str, object]]:
     target = save_file or DEFAULT_SAVE_FILE
     if not target.exists():
-        payload = {"streams": [], "settings": dict(_DEFAULT_SETTINGS)}
-        target.write_text(json.dumps(payload, indent=2), encoding="utf-8")
+        if write_back:
+            payload = {"streams": [], "settings": dict(_DEFAULT_SETTINGS)}
+            target.write_text(json.dumps(payload, indent=2), encoding="utf-8")
         return [], dict(_DEFAULT_SETTINGS)
     try:
         payload = json.loads(target.read_text(encoding="utf-8"))
@@ -119,10 +242,20 @@ def load_saved_state(
     if not isinstance(settings, dict):
         settings = {}
     normalized_settings = _normalize_settings(settings)
-    normalized_streams = [
-        str(stream).strip() for stream in streams if str(stream).strip()
-    ]
-    if normalized_settings != settings:
+    normalized_streams: list[str] = []
+    channel_qualities = dict(normalized_settings["channel_max_quality"])
+    for stream in streams:
+        channel, quality = split_channel_quality(str(stream))
+        if not channel:
+            continue
+        normalized_streams.append(channel)
+        if quality is not None:
+            channel_qualities[channel.lower()] = quality
+    normalized_settings["channel_max_quality"] = {
+        channel: quality for channel, quality in channel_qualities.items() if quality
+    }
+    changed = normalized_settings != settings or normalized_streams != streams
+    if write_back and changed:
         payload = {"streams": normalized_streams, "settings": normalized_settings}
         target.write_text(json.dumps(payload, indent=2), encoding="utf-8")
     return normalized_streams, normalized_settings
@@ -137,3 +270,56 @@ def save_state(
     normalized_settings = _normalize_settings(settings)
     payload = {"streams": streams, "settings": normalized_settings}
     target.write_text(json.dumps(payload, indent=2), encoding="utf-8")
+
+
+def split_channel_quality(text: str) -> tuple[str, int | None]:
+    channel, separator, suffix = text.strip().rpartition("@")
+    if not separator:
+        return text.strip(), None
+    suffix = suffix.strip().lower()
+    if suffix in _UNCAPPED_QUALITIES:
+        return channel.strip(), 0
+    match = _QUALITY_SUFFIX.match(suffix)
+    if match is None:
+        return text.strip(), None
+    return channel.strip(), int(match.group(1))
+
+
+def _channel_set(channels: list[object]) -> set[str]:
+    return {
+        str(channel).strip().lower()
+        for channel in channels
+        if str(channel).strip()
+    }
+
+
+def load_session_state(
+    streams: list[str],
+    session_file: Path | None = None,
+) -> dict[str, object]:
+    target = session_file or SESSION_FILE
+    if not target.exists():
+        return {}
+    try:
+        payload = json.loads(target.read_text(encoding="utf-8"))
+    except (OSError, json.JSONDecodeError):
+        return {}
+    if not isinstance(payload, dict):
+        return {}
+    saved_streams = payload.get("streams")
+    if not isinstance(saved_streams, list):
+        return {}
+    if _channel_set(saved_streams) != _channel_set(streams):
+        return {}
+    return payload
+
+
+def save_session_state(
+    streams: list[str],
+    state: dict[str, object],
+    session_file: Path | None = None,
+) -> None:
+    target = session_file or SESSION_FILE
+    payload: dict[str, object] = {"streams": list(streams)}
+    payload.update(state)
+    target.write_text(json.dumps(payload, indent=2), encoding="utf-8")
@@ -1,15 +1,38 @@
+import json
+import random
+import threading
 import time
+import urllib.request
 from dataclasses import dataclass
+from datetime import datetime
 
 from streamlink import Streamlink
+from streamlink.exceptions import NoStreamsError
 
+from .hls import (
+    fetch_playlist,
+    lowest_video_rendition,
+    parse_master_playlist,
+    select_rendition,
+)
 from .perf_log import log_perf, perf_timer
 
 _CACHE_TTL_SECONDS = 120
 _OFFLINE_CACHE_TTL_SECONDS = 600
-_URL_CACHE: dict[tuple[str, str, int], tuple[str, float]] = {}
-_NEGATIVE_CACHE: dict[tuple[str, str], float] = {}
+_TRANSIENT_CACHE_TTL_SECONDS = 30
+_DEFAULT_TIMEOUT_SECONDS = 20.0
+_URL_CACHE: dict[tuple[str, str, int, int], tuple[str, float]] = {}
+_NEGATIVE_CACHE: dict[tuple[str, str], tuple[float, float]] = {}
+_METADATA: dict[str, "StreamMetadata"] = {}
 _SESSION = Streamlink()
+_GQL_URL = "https://gql.twitch.tv/gql"
+# Twitch's public web client id, the same one streamlink sends.
+_GQL_CLIENT_ID = "kimne78kx3ncx6brgo4mv6wki5h1ko"
+_GQL_METADATA_QUERY = (
+    "query($login: String!) { user(login: $login) {"
+    " broadcastSettings { title }"
+    " stream { createdAt viewersCount game { name } } } }"
+)
 
 
 @dataclass(frozen=True)
@@ -18,12 +41,46 @@ class StreamEntry:
     url: str
 
 
+@dataclass(frozen=True)
+class StreamMetadata:
+    title: str | None
+    category: str | None
+    author: str | None
+    started_at: datetime | None = None
+    viewers: int | None = None
+
+
+@dataclass(frozen=True)
+class RetryPolicy:
+    attempts: int = 3
+    backoff_sec: float = 1.0
+    max_backoff_sec: float = 8.0
+    jitter: float = 0.25
+
+    def delay_for_attempt(self, attempt: int) -> float:
+        delay = min(
+            self.max_backoff_sec,
+            self.backoff_sec * (2 ** max(0, attempt - 1)),
+        )
+        spread = delay * self.jitter
+        return max(0.0, delay + random.uniform(-spread, spread))
+
+
+class ChannelOfflineError(RuntimeError):
+    pass
+
+
+class ResolveCancelledError(RuntimeError):
+    pass
+
+
 def _cache_key(
     channel: str,
     oauth_token: str,
     max_quality: int | None,
-) -> tuple[str, str, int]:
-    return channel.lower(), oauth_token, max_quality or -1
+    max_bandwidth_kbps: int | None = None,
+) -> tuple[str, str, int, int]:
+    return channel.lower(), oauth_token, max_quality or -1, max_bandwidth_kbps or -1
 
 
 def _neg_cache_key(channel: str, oauth_token: str) -> tuple[str, str]:
@@ -34,8 +91,9 @@ def _get_cached_url(
     channel: str,
     oauth_token: str,
     max_quality: int | None,
+    max_bandwidth_kbps: int | None = None,
 ) -> str | None:
-    key = _cache_key(channel, oauth_token, max_quality)
+    key = _cache_key(channel, oauth_token, max_quality, max_bandwidth_kbps)
     cached = _URL_CACHE.get(key)
     if not cached:
         return None
@@ -48,10 +106,11 @@ def _get_cached_url(
 
 def _get_negative_cache(channel: str, oauth_token: str) -> bool:
     key = _neg_cache_key(channel, oauth_token)
-    timestamp = _NEGATIVE_CACHE.get(key)
-    if timestamp is None:
+    cached = _NEGATIVE_CACHE.get(key)
+    if cached is None:
         return False
-    if time.time() - timestamp > _OFFLINE_CACHE_TTL_SECONDS:
+    timestamp, ttl = cached
+    if time.time() - timestamp > ttl:
         _NEGATIVE_CACHE.pop(key, None)
         return False
     return True
@@ -59,7 +118,7 @@ def _get_negative_cache(channel: str, oauth_token: str) -> bool:
 
 def _prune_url_cache(now: float | None = None) -> None:
     now = time.time() if now is None else now
-    expired: list[tuple[str, str, int]] = []
+    expired: list[tuple[str, str, int, int]] = []
     for key, (_, timestamp) in _URL_CACHE.items():
         if now - timestamp > _CACHE_TTL_SECONDS:
             expired.append(key)
@@ -70,15 +129,19 @@ def _prune_url_cache(now: float | None = None) -> None:
 def _prune_negative_cache(now: float | None = None) -> None:
     now = time.time() if now is None else now
     expired: list[tuple[str, str]] = []
-    for key, timestamp in _NEGATIVE_CACHE.items():
-        if now - timestamp > _OFFLINE_CACHE_TTL_SECONDS:
+    for key, (timestamp, ttl) in _NEGATIVE_CACHE.items():
+        if now - timestamp > ttl:
             expired.append(key)
     for key in expired:
         _NEGATIVE_CACHE.pop(key, None)
 
 
-def _mark_negative(channel: str, oauth_token: str) -> None:
-    _NEGATIVE_CACHE[_neg_cache_key(channel, oauth_token)] = time.time()
+def _mark_negative(
+    channel: str,
+    oauth_token: str,
+    ttl: float = _OFFLINE_CACHE_TTL_SECONDS,
+) -> None:
+    _NEGATIVE_CACHE[_neg_cache_key(channel, oauth_token)] = (time.time(), ttl)
 
 
 def _clear_negative(channel: str, oauth_token: str) -> None:
@@ -89,24 +152,180 @@ def resolve_hls_url(
     channel: str,
     oauth_token: str,
     max_quality: int | None = None,
+    timeout_sec: float = _DEFAULT_TIMEOUT_SECONDS,
+    max_bandwidth_kbps: int | None = None,
 ) -> str:
     print(f"resolving twitch channel '{channel}', oauth token = '{oauth_token[:4]}...'")
-    _SESSION.set_option("http-headers", {"Authorization": f"OAuth {oauth_token}"})
-    with perf_timer("stream_resolver.streams", channel=channel):
-        streams = _SESSION.streams(f"https://twitch.tv/{channel}")
-    stream = _select_stream(streams, max_quality)
-    if stream is None:
-        log_perf("stream_resolver.best_missing", channel=channel)
-        raise RuntimeError(f"streamlink could not resolve '{channel}'")
-    with perf_timer("stream_resolver.to_url", channel=channel):
-        url = stream.to_url()
-    _URL_CACHE[_cache_key(channel, oauth_token, max_quality)] = (
+    streams = _fetch_streams(channel, oauth_token, timeout_sec)
+    url = _select_rendition_url(
+        channel,
+        streams,
+        max_quality,
+        max_bandwidth_kbps,
+        timeout_sec,
+    )
+    if url is None:
+        stream = _select_stream(streams, max_quality)
+        if stream is None:
+            log_perf("stream_resolver.best_missing", channel=channel)
+            raise ChannelOfflineError(
+                f"streamlink could not resolve '{channel}'"
+            )
+        with perf_timer("stream_resolver.to_url", channel=channel):
+            url = stream.to_url()
+    key = _cache_key(channel, oauth_token, max_quality, max_bandwidth_kbps)
+    _URL_CACHE[key] = (
         url,
         time.time(),
     )
     return url
 
 
+def refresh_stream_metadata(
+    channel: str,
+    oauth_token: str,
+    timeout_sec: float = _DEFAULT_TIMEOUT_SECONDS,
+) -> StreamMetadata | None:
+    # One small GQL query instead of a full stream resolve per channel.
+    with perf_timer("stream_resolver.metadata", channel=channel):
+        user = _fetch_gql_user(channel, oauth_token, timeout_sec)
+    stream = user.get("stream") if isinstance(user, dict) else None
+    if not isinstance(stream, dict):
+        _METADATA.pop(channel.lower(), None)
+        return None
+    settings = user.get("broadcastSettings")
+    game = stream.get("game")
+    previous = _METADATA.get(channel.lower())
+    metadata = StreamMetadata(
+        title=settings.get("title") if isinstance(settings, dict) else None,
+        category=game.get("name") if isinstance(game, dict) else None,
+        author=previous.author if previous is not None else channel,
+        started_at=_parse_timestamp(stream.get("createdAt")),
+        viewers=_parse_int(stream.get("viewersCount")),
+    )
+    _METADATA[channel.lower()] = metadata
+    return metadata
+
+
+def _fetch_gql_user(
+    channel: str,
+    oauth_token: str,
+    timeout_sec: float,
+) -> object:
+    headers = {
+        "Client-ID": _GQL_CLIENT_ID,
+        "Content-Type": "application/json",
+        "User-Agent": "trs",
+    }
+    if oauth_token:
+        headers["Authorization"] = f"OAuth {oauth_token}"
+    body = json.dumps(
+        {"query": _GQL_METADATA_QUERY, "variables": {"login": channel.lower()}}
+    ).encode("utf-8")
+    request = urllib.request.Request(_GQL_URL, data=body, headers=headers)
+    with urllib.request.urlopen(request, timeout=timeout_sec) as response:
+        payload = json.loads(response.read().decode("utf-8"))
+    data = payload.get("data") if isinstance(payload, dict) else None
+    return data.get("user") if isinstance(data, dict) else None
+
+
+def _parse_timestamp(value: object) -> datetime | None:
+    if not isinstance(value, str) or not value:
+        return None
+    try:
+        return datetime.fromisoformat(value.replace("Z", "+00:00"))
+    except ValueError:
+        return None
+
+
+def _parse_int(value: object) -> int | None:
+    try:
+        return int(value)
+    except (TypeError, ValueError):
+        return None
+
+
+def get_stream_metadata(channel: str) -> StreamMetadata | None:
+    return _METADATA.get(channel.lower())
+
+
+def _fetch_streams(
+    channel: str,
+    oauth_token: str,
+    timeout_sec: float,
+) -> dict[str, object]:
+    _SESSION.set_option("http-headers", {"Authorization": f"OAuth {oauth_token}"})
+    _SESSION.set_option("http-timeout", timeout_sec)
+    with perf_timer("stream_resolver.streams", channel=channel):
+        # Go through the plugin directly so its title/category can be read.
+        _, plugin_class, resolved_url = _SESSION.resolve_url(
+            f"https://twitch.tv/{channel}"
+        )
+        plugin = plugin_class(_SESSION, resolved_url)
+        try:
+            streams = plugin.streams()
+        except NoStreamsError as exc:
+            raise ChannelOfflineError(f"'{channel}' is offline") from exc
+    if not streams:
+        raise ChannelOfflineError(f"'{channel}' is offline")
+    _METADATA[channel.lower()] = StreamMetadata(
+        title=plugin.get_title(),
+        category=plugin.get_category(),
+        author=plugin.get_author(),
+    )
+    return streams
+
+
+def _select_rendition_url(
+    channel: str,
+    streams: dict[str, object],
+    max_quality: int | None,
+    max_bandwidth_kbps: int | None,
+    timeout_sec: float,
+) -> str | None:
+    master_url = _master_playlist_url(streams)
+    if not master_url:
+        return None
+    try:
+        with perf_timer("stream_resolver.master_playlist", channel=channel):
+            renditions = parse_master_playlist(
+                fetch_playlist(master_url, timeout=timeout_sec),
+                master_url,
+            )
+    except Exception as exc:
+        log_perf(
+            "stream_resolver.master_playlist_failed",
+            channel=channel,
+            error=type(exc).__name__,
+        )
+        return None
+    rendition = select_rendition(renditions, max_quality, max_bandwidth_kbps)
+    if rendition is None and max_bandwidth_kbps:
+        # Nothing fits the cap, so take the cheapest video rather than the best.
+        rendition = lowest_video_rendition(renditions)
+    if rendition is None:
+        return None
+    log_perf(
+        "stream_resolver.rendition",
+        channel=channel,
+        name=rendition.name,
+        height=rendition.height,
+        bandwidth=rendition.bandwidth,
+    )
+    return rendition.url
+
+
+def _master_playlist_url(streams: dict[str, object]) -> str | None:
+    for stream in streams.values():
+        master_url = getattr(stream, "url_master", None)
+        if not master_url:
+            multivariant = getattr(stream, "multivariant", None)
+            master_url = getattr(multivariant, "uri", None)
+        if isinstance(master_url, str) and master_url:
+            return master_url
+    return None
+
+
 def _select_stream(
     streams: dict[str, object],
     max_quality: int | None,
@@ -143,17 +362,98 @@ def _parse_quality_height(name: str) -> int | None:
         return None
 
 
+def _resolve_with_retry(
+    channel: str,
+    oauth_token: str,
+    max_quality: int | None,
+    retry_policy: RetryPolicy,
+    timeout_sec: float,
+    max_bandwidth_kbps: int | None,
+    cancel_event: threading.Event | None,
+) -> str:
+    # timeout_sec bounds the whole resolve, retries and backoff included.
+    deadline = time.monotonic() + timeout_sec
+    attempt = 1
+    while True:
+        try:
+            return resolve_hls_url(
+                channel,
+                oauth_token,
+                max_quality,
+                timeout_sec=max(0.1, deadline - time.monotonic()),
+                max_bandwidth_kbps=max_bandwidth_kbps,
+            )
+        except ChannelOfflineError:
+            raise
+        except Exception as exc:
+            if attempt >= retry_policy.attempts:
+                raise
+            delay = retry_policy.delay_for_attempt(attempt)
+            if time.monotonic() + delay >= deadline:
+                log_perf("stream_resolver.deadline", channel=channel)
+                raise TimeoutError(
+                    f"resolving '{channel}' took over {timeout_sec:.0f}s"
+                ) from exc
+            log_perf(
+                "stream_resolver.retry",
+                channel=channel,
+                attempt=attempt,
+                delay_sec=f"{delay:.2f}",
+                error=type(exc).__name__,
+            )
+            if cancel_event is None:
+                time.sleep(delay)
+            elif cancel_event.wait(delay):
+                raise ResolveCancelledError(channel) from exc
+            attempt += 1
+
+
+def invalidate_channel(channel: str, oauth_token: str) -> None:
+    channel_key = channel.lower()
+    stale = [
+        key for key in _URL_CACHE if key[:2] == (channel_key, oauth_token)
+    ]
+    for key in stale:
+        _URL_CACHE.pop(key, None)
+
+
+def mark_channel_failed(channel: str, oauth_token: str) -> None:
+    invalidate_channel(channel, oauth_token)
+    _mark_negative(channel, oauth_token)
+    log_perf("stream_resolver.channel_failed", channel=channel)
+
+
 def resolve_channel_urls(
     channels: list[str],
     oauth_token: str,
     max_quality: int | None = None,
+    quality_overrides: dict[str, int] | None = None,
+    retry_policy: RetryPolicy | None = None,
+    timeout_sec: float = _DEFAULT_TIMEOUT_SECONDS,
+    max_bandwidth_kbps: int | None = None,
+    cancel_event: threading.Event | None = None,
+    timings: dict[str, float] | None = None,
+    retry_overrides: dict[str, RetryPolicy] | None = None,
 ) -> list[StreamEntry]:
     urls: list[StreamEntry] = []
+    retry_policy = retry_policy or RetryPolicy()
+    retry_overrides = retry_overrides or {}
     _prune_url_cache()
     _prune_negative_cache()
+    quality_overrides = quality_overrides or {}
     for channel in channels:
+        if cancel_event is not None and cancel_event.is_set():
+            log_perf("stream_resolver.cancelled", channel=channel)
+            break
+        channel_quality = quality_overrides.get(channel.lower(), max_quality)
+        channel_start = time.perf_counter()
         try:
-            cached = _get_cached_url(channel, oauth_token, max_quality)
+            cached = _get_cached_url(
+                channel,
+                oauth_token,
+                channel_quality,
+                max_bandwidth_kbps,
+            )
             if cached:
                 log_perf("stream_resolver.cache_hit", channel=channel)
                 urls.append(StreamEntry(channel=channel, url=cached))
@@ -163,15 +463,37 @@ def resolve_channel_urls(
                 continue
             log_perf("stream_resolver.cache_miss", channel=channel)
             with perf_timer("stream_resolver.resolve_hls_url", channel=channel):
-                url = resolve_hls_url(channel, oauth_token, max_quality)
+                url = _resolve_with_retry(
+                    channel,
+                    oauth_token,
+                    channel_quality,
+                    retry_overrides.get(channel.lower(), retry_policy),
+                    timeout_sec,
+                    max_bandwidth_kbps,
+                    cancel_event,
+                )
             _clear_negative(channel, oauth_token)
             urls.append(StreamEntry(channel=channel, url=url))
-        except Exception as exc:
+        except ResolveCancelledError:
+            log_perf("stream_resolver.cancelled", channel=channel)
+            break
+        except ChannelOfflineError as exc:
             _mark_negative(channel, oauth_token)
+            log_perf("stream_resolver.offline", channel=channel)
+            print(f"stream offline for '{channel}': {exc}")
+        except Exception as exc:
+            _mark_negative(
+                channel,
+                oauth_token,
+                ttl=_TRANSIENT_CACHE_TTL_SECONDS,
+            )
             log_perf(
                 "stream_resolver.resolve_failed",
                 channel=channel,
                 error=type(exc).__name__,
             )
             print(f"stream resolve failed for '{channel}': {exc}")
+        finally:
+            if timings is not None:
+                timings[channel] = (time.perf_counter() - channel_start) * 1000.0
     return urls
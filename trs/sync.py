@@ -0,0 +1,135 @@
+import json
+import uuid
+
+from PySide6 import QtCore, QtNetwork
+
+from .perf_log import log_perf
+
+SYNC_ROLES = ("leader", "follower")
+DEFAULT_SYNC_ADDRESS = "239.255.42.99"
+DEFAULT_SYNC_PORT = 45454
+_REBROADCAST_INTERVAL_MS = 2_000
+
+
+class SyncLeader(QtCore.QObject):
+    def __init__(
+        self,
+        address: str,
+        port: int,
+        parent: QtCore.QObject | None = None,
+    ) -> None:
+        super().__init__(parent)
+        self._address = QtNetwork.QHostAddress(address)
+        self._port = port
+        self._socket = QtNetwork.QUdpSocket(self)
+        self._socket.setSocketOption(
+            QtNetwork.QAbstractSocket.MulticastTtlOption,
+            1,
+        )
+        self._session = uuid.uuid4().hex
+        self._sequence = 0
+        self._payload: bytes | None = None
+        # Followers that start late pick up the current state on the next resend.
+        self._timer = QtCore.QTimer(self)
+        self._timer.setInterval(_REBROADCAST_INTERVAL_MS)
+        self._timer.timeout.connect(self._send)
+        self._timer.start()
+
+    def publish(
+        self,
+        channels: list[str],
+        focused: bool,
+        manual_layout: bool,
+    ) -> None:
+        self._sequence += 1
+        self._payload = json.dumps(
+            {
+                "session": self._session,
+                "seq": self._sequence,
+                "channels": list(channels),
+                "focused": focused,
+                "manual_layout": manual_layout,
+            }
+        ).encode("utf-8")
+        self._send()
+
+    def stop(self) -> None:
+        self._timer.stop()
+        self._socket.close()
+
+    def _send(self) -> None:
+        if self._payload is None:
+            return
+        written = self._socket.writeDatagram(
+            self._payload,
+            self._address,
+            self._port,
+        )
+        if written < 0:
+            log_perf("sync.send_failed", error=self._socket.errorString())
+
+
+class SyncFollower(QtCore.QObject):
+    state_received = QtCore.Signal(list, bool, bool)
+
+    def __init__(
+        self,
+        address: str,
+        port: int,
+        parent: QtCore.QObject | None = None,
+    ) -> None:
+        super().__init__(parent)
+        self._address = QtNetwork.QHostAddress(address)
+        self._socket = QtNetwork.QUdpSocket(self)
+        self._leader_session = ""
+        self._last_sequence = 0
+        self._last_state: tuple[list[str], bool, bool] | None = None
+        bound = self._socket.bind(
+            QtNetwork.QHostAddress(QtNetwork.QHostAddress.AnyIPv4),
+            port,
+            QtNetwork.QAbstractSocket.ShareAddress
+            | QtNetwork.QAbstractSocket.ReuseAddressHint,
+        )
+        if not bound or not self._socket.joinMulticastGroup(self._address):
+            print(
+                f"sync follower could not listen on {address}:{port}: "
+                f"{self._socket.errorString()}"
+            )
+        self._socket.readyRead.connect(self._read_pending)
+
+    def last_state(self) -> tuple[list[str], bool, bool] | None:
+        return self._last_state
+
+    def stop(self) -> None:
+        self._socket.leaveMulticastGroup(self._address)
+        self._socket.close()
+
+    def _read_pending(self) -> None:
+        while self._socket.hasPendingDatagrams():
+            datagram = self._socket.receiveDatagram()
+            self._handle_datagram(bytes(datagram.data()))
+
+    def _handle_datagram(self, data: bytes) -> None:
+        try:
+            payload = json.loads(data.decode("utf-8"))
+            session = str(payload["session"])
+            sequence = int(payload["seq"])
+            channels = [str(channel) for channel in payload["channels"]]
+            focused = bool(payload["focused"])
+            manual_layout = bool(payload["manual_layout"])
+        except (ValueError, KeyError, TypeError):
+            log_perf("sync.bad_datagram", size=len(data))
+            return
+        if session != self._leader_session:
+            # A restarted leader counts from 1 again.
+            self._leader_session = session
+            self._last_sequence = 0
+        if sequence < self._last_sequence:
+            return
+        self._last_sequence = sequence
+        state = (channels, focused, manual_layout)
+        if state == self._last_state:
+            return
+        self._last_state = state
+        log_perf("sync.state_received", seq=sequence, count=len(channels))
+        self.state_received.emit(channels, focused, manual_layout)
@@ -0,0 +1,87 @@
+import re
+import time
+
+from PySide6 import QtCore, QtGui
+
+from .stream_resolver import StreamEntry
+
+TEST_SOURCE_SCHEME = "trs-test"
+# Hyphens are not allowed in Twitch logins, so these never clash with a channel.
+_TEST_CHANNEL = re.compile(r"^test-\d+$")
+_FRAME_SIZE = QtCore.QSize(640, 360)
+_FRAME_INTERVAL_MS = 33
+_BAR_COLORS = (
+    "#c0c0c0",
+    "#c0c000",
+    "#00c0c0",
+    "#00c000",
+    "#c000c0",
+    "#c00000",
+    "#0000c0",
+)
+
+
+def test_channel_names(count: int) -> list[str]:
+    return [f"test-{index}" for index in range(1, max(0, count) + 1)]
+
+
+def is_test_channel(channel: str) -> bool:
+    return bool(_TEST_CHANNEL.match(channel.strip().lower()))
+
+
+def is_test_source(url: str) -> bool:
+    return QtCore.QUrl(url).scheme() == TEST_SOURCE_SCHEME
+
+
+def test_stream_entries(channels: list[str]) -> list[StreamEntry]:
+    return [
+        StreamEntry(channel=channel, url=f"{TEST_SOURCE_SCHEME}:{channel}")
+        for channel in channels
+        if is_test_channel(channel)
+    ]
+
+
+class TestPattern(QtCore.QObject):
+    frame_ready = QtCore.Signal(QtGui.QImage)
+
+    def __init__(self, label: str, parent: QtCore.QObject | None = None) -> None:
+        super().__init__(parent)
+        self._label = label
+        digits = "".join(char for char in label if char.isdigit())
+        # Each source gets its own hue so tiles are easy to tell apart.
+        self._hue = (int(digits or 0) * 47) % 360
+        self._started = time.monotonic()
+        self._timer = QtCore.QTimer(self)
+        self._timer.setInterval(_FRAME_INTERVAL_MS)
+        self._timer.timeout.connect(self._render)
+        self._timer.start()
+
+    def _render(self) -> None:
+        elapsed = time.monotonic() - self._started
+        image = QtGui.QImage(_FRAME_SIZE, QtGui.QImage.Format_RGB32)
+        image.fill(QtGui.QColor.fromHsv(self._hue, 160, 70))
+        painter = QtGui.QPainter(image)
+        width = image.width()
+        height = image.height()
+        bar_width = width / len(_BAR_COLORS)
+        for index, color in enumerate(_BAR_COLORS):
+            painter.fillRect(
+                QtCore.QRectF(index * bar_width, 0, bar_width, height * 0.6),
+                QtGui.QColor(color),
+            )
+        # A sweeping bar makes frozen or dropped frames obvious.
+        sweep_x = (elapsed * 160) % width
+        painter.fillRect(QtCore.QRectF(sweep_x, 0, 8, height), QtCore.Qt.white)
+        painter.setPen(QtCore.Qt.white)
+        font = painter.font()
+        font.setPixelSize(40)
+        font.setBold(True)
+        painter.setFont(font)
+        text_rect = QtCore.QRect(0, int(height * 0.6), width, int(height * 0.4))
+        painter.drawText(
+            text_rect,
+            QtCore.Qt.AlignCenter,
+            f"{self._label}\n{elapsed:7.1f}s",
+        )
+        painter.end()
+        self.frame_ready.emit(image)
@@ -0,0 +1,67 @@
+import hmac
+
+from PySide6 import QtCore, QtNetwork
+
+from .perf_log import log_perf
+
+CONTROL_COMMANDS = (
+    "focus",
+    "next",
+    "previous",
+    "unfocus",
+    "pause",
+    "resume",
+    "blank",
+    "unblank",
+)
+
+
+class UdpControl(QtCore.QObject):
+    command_received = QtCore.Signal(str, str)
+
+    def __init__(
+        self,
+        port: int,
+        secret: str,
+        parent: QtCore.QObject | None = None,
+    ) -> None:
+        super().__init__(parent)
+        self._secret = secret.encode("utf-8")
+        self._socket = QtNetwork.QUdpSocket(self)
+        if not self._socket.bind(
+            QtNetwork.QHostAddress(QtNetwork.QHostAddress.AnyIPv4),
+            port,
+        ):
+            print(
+                f"udp control could not listen on port {port}: "
+                f"{self._socket.errorString()}"
+            )
+        self._socket.readyRead.connect(self._read_pending)
+
+    def stop(self) -> None:
+        self._socket.close()
+
+    def _read_pending(self) -> None:
+        while self._socket.hasPendingDatagrams():
+            datagram = self._socket.receiveDatagram()
+            self._handle_datagram(
+                bytes(datagram.data()),
+                datagram.senderAddress().toString(),
+            )
+
+    def _handle_datagram(self, data: bytes, sender: str) -> None:
+        # "<secret> <command> [argument]", as sent by Companion's generic UDP.
+        parts = data.decode("utf-8", errors="replace").strip().split(None, 2)
+        if len(parts) < 2 or not hmac.compare_digest(
+            parts[0].encode("utf-8"),
+            self._secret,
+        ):
+            log_perf("udp_control.rejected", sender=sender)
+            return
+        command = parts[1].lower()
+        argument = parts[2].strip() if len(parts) > 2 else ""
+        if command not in CONTROL_COMMANDS:
+            print(f"unknown udp control command '{command}' from {sender}")
+            return
+        log_perf("udp_control.command", command=command, sender=sender)
+        self.command_received.emit(command, argument)
@@ -1,3 +1,4 @@
+import re
 from pathlib import Path
 
 from PySide6 import QtCore, QtGui, QtMultimedia, QtWidgets
@@ -6,9 +7,25 @@ from ..config import (
     CONTROL_PANEL_TITLE,
     PACE_AUTOFOCUS_THRESHOLD,
     PACE_PACEMAN_THRESHOLD,
+    QUALITY_STEPS,
+)
+from ..content_filter import ContentFilter
+from ..lineup import (
+    LineupSlot,
+    active_lineup_slots,
+    load_lineup,
+    next_lineup_slot,
+)
+from ..mcsr_ranked import (
+    LIVE_PREFIX,
+    RANKED_PREFIX,
+    RankedLiveMatch,
+    fetch_live_match,
+    resolve_ranked_channels,
 )
 from ..paceman import PacemanRun, fetch_live_runs, set_pace_config
 from ..perf_log import log_perf, perf_timer
+from ..storage import split_channel_quality
 
 _ICON_NAME_BY_EVENT = {
     "rsg.enter_end": "end.webp",
@@ -19,7 +36,7 @@ _ICON_NAME_BY_EVENT = {
     "rsg.enter_nether": "nether.webp",
 }
 
-_QUALITY_STEPS = [160, 360, 480, 720, 1080]
+_MAX_VIDEO_DELAY_MS = 5000
 
 
 class _PacemanWorkerSignals(QtCore.QObject):
@@ -42,6 +59,46 @@ class _PacemanWorker(QtCore.QRunnable):
         self.signals.finished.emit(runs)
 
 
+class _RankedImportWorkerSignals(QtCore.QObject):
+    finished = QtCore.Signal(list)
+    error = QtCore.Signal(str)
+
+
+class _RankedImportWorker(QtCore.QRunnable):
+    def __init__(self, query: str) -> None:
+        super().__init__()
+        self._query = query
+        self.signals = _RankedImportWorkerSignals()
+
+    def run(self) -> None:
+        try:
+            channels = resolve_ranked_channels(self._query)
+        except Exception as exc:
+            self.signals.error.emit(str(exc))
+            return
+        self.signals.finished.emit(channels)
+
+
+class _RankedLiveWorkerSignals(QtCore.QObject):
+    finished = QtCore.Signal(object)
+    error = QtCore.Signal(str)
+
+
+class _RankedLiveWorker(QtCore.QRunnable):
+    def __init__(self, names: list[str]) -> None:
+        super().__init__()
+        self._names = names
+        self.signals = _RankedLiveWorkerSignals()
+
+    def run(self) -> None:
+        try:
+            match = fetch_live_match(self._names)
+        except Exception as exc:
+            self.signals.error.emit(str(exc))
+            return
+        self.signals.finished.emit(match)
+
+
 class _FlowLayout(QtWidgets.QLayout):
     def __init__(
         self,
@@ -128,6 +185,7 @@ class ControlPanelWindow(QtWidgets.QWidget):
     settings_changed = QtCore.Signal(dict)
     fullscreen_toggled = QtCore.Signal(bool)
     overlay_info_changed = QtCore.Signal(dict, bool)
+    focus_changed = QtCore.Signal(object, object, str)
 
     def __init__(
         self,
@@ -144,6 +202,7 @@ class ControlPanelWindow(QtWidgets.QWidget):
         self._paceman_loading = False
         self._pending_paceman_refresh = False
         self._include_hidden = False
+        self._content_filter = ContentFilter()
         self._paceman_fallback = False
         self._paceman_event_slug = ""
         self._hide_offline = False
@@ -155,14 +214,28 @@ class ControlPanelWindow(QtWidgets.QWidget):
         self._pace_autofocus_threshold = PACE_AUTOFOCUS_THRESHOLD
         self._pace_paceman_enabled = False
         self._pace_paceman_threshold = PACE_PACEMAN_THRESHOLD
-        self._max_stream_quality = _QUALITY_STEPS[3]
+        self._max_stream_quality = QUALITY_STEPS[3]
+        self._video_delay_ms = 0
+        self._channel_video_delays_ms: dict[str, int] = {}
+        self._channel_max_quality: dict[str, int] = {}
         self._focus_bell_enabled = False
+        self._ad_mute_enabled = False
+        self._show_offline_placeholders = False
         self._pace_good_splits: dict[str, float] = {}
         self._pace_progression_bonus: dict[str, float] = {}
         self._focused_channel: str | None = None
+        self._reported_focus: str | None = None
         self._auto_focus_active = False
+        self._return_focus_channel: str | None = None
+        self._channel_live: dict[str, bool] = {}
         self._current_worker: _PacemanWorker | None = None
         self._thread_pool = QtCore.QThreadPool.globalInstance()
+        self._ranked_worker: _RankedImportWorker | None = None
+        self._ranked_live_worker: _RankedLiveWorker | None = None
+        self._ranked_live_names: list[str] = []
+        self._ranked_live_channels: list[str] = []
+        self._ranked_live_added: list[str] = []
+        self._ranked_live_events: dict[str, str] = {}
         self._bell_effect = QtMultimedia.QSoundEffect(self)
         bell_path = (
             Path(__file__).resolve().parent.parent
@@ -182,6 +255,14 @@ class ControlPanelWindow(QtWidgets.QWidget):
         self._paceman_timer = QtCore.QTimer(self)
         self._paceman_timer.setInterval(10_000)
         self._paceman_timer.timeout.connect(self._start_paceman_refresh)
+        self._lineup_file = ""
+        self._lineup_slots: list[LineupSlot] = []
+        self._lineup_timer = QtCore.QTimer(self)
+        self._lineup_timer.setInterval(15_000)
+        self._lineup_timer.timeout.connect(self._on_lineup_tick)
+        self._ranked_live_timer = QtCore.QTimer(self)
+        self._ranked_live_timer.setInterval(10_000)
+        self._ranked_live_timer.timeout.connect(self._start_ranked_live_poll)
         self._last_active_streams: list[str] = []
         self._last_active_focused = False
         self._last_active_quality: int | None = None
@@ -223,6 +304,10 @@ class ControlPanelWindow(QtWidgets.QWidget):
         self._fullscreen_toggle.toggled.connect(self._toggle_fullscreen)
         self._overlay_toggle = QtWidgets.QCheckBox("Show overlay", self)
         self._overlay_toggle.toggled.connect(self._toggle_overlay)
+        self._ad_mute_toggle = QtWidgets.QCheckBox("Mute ads", self)
+        self._ad_mute_toggle.toggled.connect(self._toggle_ad_mute)
+        self._offline_cards_toggle = QtWidgets.QCheckBox("Offline cards", self)
+        self._offline_cards_toggle.toggled.connect(self._toggle_offline_cards)
         options_flow.addWidget(self._paceman_toggle)
         options_flow.addWidget(self._show_hidden_toggle)
         options_flow.addWidget(self._hide_offline_toggle)
@@ -231,6 +316,8 @@ class ControlPanelWindow(QtWidgets.QWidget):
         options_flow.addWidget(self._focus_bell_toggle)
         options_flow.addWidget(self._clear_focus_button)
         options_flow.addWidget(self._overlay_toggle)
+        options_flow.addWidget(self._ad_mute_toggle)
+        options_flow.addWidget(self._offline_cards_toggle)
         options_flow.addWidget(self._fullscreen_toggle)
         layout.addLayout(options_flow)
 
@@ -248,7 +335,7 @@ class ControlPanelWindow(QtWidgets.QWidget):
         quality_row = QtWidgets.QHBoxLayout()
         self._quality_label = QtWidgets.QLabel("Max quality", self)
         self._quality_slider = QtWidgets.QSlider(QtCore.Qt.Horizontal, self)
-        self._quality_slider.setRange(0, len(_QUALITY_STEPS) - 1)
+        self._quality_slider.setRange(0, len(QUALITY_STEPS) - 1)
         self._quality_slider.setSingleStep(1)
         self._quality_slider.setPageStep(1)
         self._quality_slider.valueChanged.connect(self._update_quality)
@@ -258,6 +345,18 @@ class ControlPanelWindow(QtWidgets.QWidget):
         quality_row.addWidget(self._quality_value)
         layout.addLayout(quality_row)
 
+        delay_row = QtWidgets.QHBoxLayout()
+        self._delay_label = QtWidgets.QLabel("Video delay", self)
+        self._delay_input = QtWidgets.QSpinBox(self)
+        self._delay_input.setRange(0, _MAX_VIDEO_DELAY_MS)
+        self._delay_input.setSingleStep(100)
+        self._delay_input.setSuffix(" ms")
+        self._delay_input.valueChanged.connect(self._update_video_delay)
+        delay_row.addWidget(self._delay_label)
+        delay_row.addWidget(self._delay_input)
+        delay_row.addStretch(1)
+        layout.addLayout(delay_row)
+
         self._status_label = QtWidgets.QLabel("", self)
         self._status_label.setWordWrap(True)
         layout.addWidget(self._status_label)
@@ -283,7 +382,9 @@ class ControlPanelWindow(QtWidgets.QWidget):
         manual_layout.setContentsMargins(0, 0, 0, 0)
         manual_layout.setSpacing(8)
         self._input = QtWidgets.QLineEdit(self._manual_tab)
-        self._input.setPlaceholderText("twitch channel name")
+        self._input.setPlaceholderText(
+            "twitch channel, ranked:<players> or ranked:match:<id>"
+        )
         self._add_button = QtWidgets.QPushButton("Add Stream", self._manual_tab)
         self._add_button.clicked.connect(self._add_stream)
         self._input.returnPressed.connect(self._add_stream)
@@ -319,6 +420,11 @@ class ControlPanelWindow(QtWidgets.QWidget):
         manual_grid_row.addStretch(1)
         manual_layout.addLayout(manual_grid_row)
 
+        self._lineup_label = QtWidgets.QLabel("", self._manual_tab)
+        self._lineup_label.setWordWrap(True)
+        self._lineup_label.setVisible(False)
+        manual_layout.addWidget(self._lineup_label)
+
         self._manual_list = QtWidgets.QListWidget(self._manual_tab)
         manual_layout.addWidget(self._manual_list, 1)
 
@@ -439,6 +545,7 @@ class ControlPanelWindow(QtWidgets.QWidget):
                 self._paceman_timer.stop()
                 if self._focused_channel not in self._manual_streams:
                     self._focused_channel = None
+                    self._report_focus_change("paceman_disabled")
                 self._clear_focus_button.setEnabled(
                     self._focused_channel is not None
                 )
@@ -510,6 +617,38 @@ class ControlPanelWindow(QtWidgets.QWidget):
     def manual_grid_limits(self) -> tuple[int, int]:
         return self._current_manual_grid_limits()
 
+    def focus_channel(self, channel: str) -> None:
+        if self._channel_key(channel) != self._channel_key(self._focused_channel):
+            self._set_focus(channel)
+
+    def clear_focus(self) -> None:
+        self._clear_focus()
+
+    def cycle_focus(self, step: int, displayed: list[str]) -> None:
+        # Walk the source order; the displayed order puts the focus first.
+        source = self._lineup_channels() or list(self._manual_streams)
+        if not self.is_manual_source_active():
+            # Paceman fallback can put the manual list on screen instead.
+            source = [
+                run.channel for run in self._sorted_paceman_runs() if run.channel
+            ] + source
+        shown = {self._channel_key(channel) for channel in displayed}
+        order = [
+            channel for channel in source if self._channel_key(channel) in shown
+        ]
+        if not order:
+            return
+        keys = [self._channel_key(channel) for channel in order]
+        current = self._channel_key(self._focused_channel)
+        if current in keys:
+            index = (keys.index(current) + step) % len(order)
+        else:
+            index = 0 if step > 0 else len(order) - 1
+        self.focus_channel(order[index])
+
+    def set_pace_autofocus(self, enabled: bool) -> None:
+        self._pace_autofocus_toggle.setChecked(enabled)
+
     def force_refresh_active_streams(self) -> None:
         self._last_active_streams = []
         self._last_active_focused = False
@@ -536,6 +675,17 @@ class ControlPanelWindow(QtWidgets.QWidget):
         self._emit_settings()
         self._emit_overlay_info()
 
+    def _toggle_ad_mute(self, enabled: bool) -> None:
+        self._ad_mute_enabled = enabled
+        self._emit_settings()
+
+    def _toggle_offline_cards(self, enabled: bool) -> None:
+        if enabled == self._show_offline_placeholders:
+            return
+        self._show_offline_placeholders = enabled
+        self._emit_settings()
+        self.force_refresh_active_streams()
+
     def _toggle_focus_bell(self, enabled: bool) -> None:
         self._focus_bell_enabled = enabled
         self._emit_settings()
@@ -582,12 +732,26 @@ class ControlPanelWindow(QtWidgets.QWidget):
             self._emit_active_streams()
 
     def _update_quality(self, slider_value: int) -> None:
-        index = max(0, min(slider_value, len(_QUALITY_STEPS) - 1))
-        self._max_stream_quality = _QUALITY_STEPS[index]
+        index = max(0, min(slider_value, len(QUALITY_STEPS) - 1))
+        self._max_stream_quality = QUALITY_STEPS[index]
         self._quality_value.setText(f"{self._max_stream_quality}p")
         self._emit_settings()
         self._emit_active_streams()
 
+    def _update_video_delay(self, value: int) -> None:
+        self._video_delay_ms = int(value)
+        self._emit_settings()
+
+    def _update_channel_video_delay(self, channel: str, value: int) -> None:
+        channel_key = self._channel_key(channel)
+        if not channel_key:
+            return
+        if value > 0:
+            self._channel_video_delays_ms[channel_key] = int(value)
+        else:
+            self._channel_video_delays_ms.pop(channel_key, None)
+        self._emit_settings()
+
     def _start_paceman_refresh(self) -> None:
         if self._paceman_loading:
             self._pending_paceman_refresh = True
@@ -633,8 +797,28 @@ class ControlPanelWindow(QtWidgets.QWidget):
         channel = self._input.text().strip()
         if not channel:
             return
+        if channel.lower().startswith(RANKED_PREFIX):
+            query = channel[len(RANKED_PREFIX):]
+            if query.lower().startswith(LIVE_PREFIX):
+                self._start_ranked_follow(query[len(LIVE_PREFIX):])
+            else:
+                self._start_ranked_import(query)
+            return
+        channel, quality = split_channel_quality(channel)
+        if not channel:
+            self._status_label.setText("Enter a channel name before the @quality.")
+            return
+        if quality is not None:
+            if quality:
+                self._channel_max_quality[self._channel_key(channel)] = quality
+            else:
+                self._channel_max_quality.pop(self._channel_key(channel), None)
+            self._emit_settings()
         if channel in self._manual_streams:
             self._input.clear()
+            self._refresh_list()
+            # Caps aren't part of the active-stream dedup, so force a resolve.
+            self.force_refresh_active_streams()
             return
         self._manual_streams.append(channel)
         self._input.clear()
@@ -642,6 +826,164 @@ class ControlPanelWindow(QtWidgets.QWidget):
         self.manual_streams_changed.emit(list(self._manual_streams))
         self._emit_active_streams()
 
+    def _start_ranked_import(self, query: str) -> None:
+        if self._ranked_worker is not None or not query.strip():
+            return
+        worker = _RankedImportWorker(query)
+        worker.signals.finished.connect(self._on_ranked_import)
+        worker.signals.error.connect(self._on_ranked_import_error)
+        self._ranked_worker = worker
+        self._add_button.setEnabled(False)
+        self._status_label.setText("Looking up MCSR Ranked players...")
+        self._thread_pool.start(worker)
+
+    def _on_ranked_import(self, channels: list[str]) -> None:
+        self._ranked_worker = None
+        self._add_button.setEnabled(True)
+        self._input.clear()
+        added = [
+            channel
+            for channel in channels
+            if channel not in self._manual_streams
+            and self._content_filter.allows(channel)
+        ]
+        if not channels:
+            self._status_label.setText("No Twitch channels found for that query.")
+            return
+        if not added:
+            self._status_label.setText(
+                f"Ranked channels already listed: {', '.join(channels)}"
+            )
+            return
+        self._status_label.setText(f"Added ranked channels: {', '.join(added)}")
+        self._manual_streams.extend(added)
+        self._refresh_list()
+        self.manual_streams_changed.emit(list(self._manual_streams))
+        self._emit_active_streams()
+
+    def _on_ranked_import_error(self, message: str) -> None:
+        self._ranked_worker = None
+        self._add_button.setEnabled(True)
+        self._status_label.setText(f"MCSR Ranked lookup failed: {message}")
+
+    def _start_ranked_follow(self, query: str) -> None:
+        names = [name for name in re.split(r"[\s,]+", query) if name]
+        if not names:
+            return
+        if self._ranked_live_names:
+            self._end_ranked_follow("")
+        self._ranked_live_names = names
+        self._input.clear()
+        self._status_label.setText(
+            f"Waiting for a live MCSR Ranked match with {', '.join(names)}..."
+        )
+        self._ranked_live_timer.start()
+        self._start_ranked_live_poll()
+
+    def _start_ranked_live_poll(self) -> None:
+        if self._ranked_live_worker is not None or not self._ranked_live_names:
+            return
+        worker = _RankedLiveWorker(list(self._ranked_live_names))
+        worker.signals.finished.connect(self._on_ranked_live)
+        worker.signals.error.connect(self._on_ranked_live_error)
+        self._ranked_live_worker = worker
+        self._thread_pool.start(worker)
+
+    def _on_ranked_live(self, match: RankedLiveMatch | None) -> None:
+        self._ranked_live_worker = None
+        if not self._ranked_live_names:
+            return
+        if match is None:
+            # Finished matches drop off the live list.
+            if self._ranked_live_channels:
+                self._end_ranked_follow("Ranked match finished.")
+            return
+        if not self._ranked_live_channels:
+            self._ranked_live_channels = [
+                channel
+                for channel in match.channels
+                if self._content_filter.allows(channel)
+            ]
+            if not self._ranked_live_channels:
+                # Nothing to show or follow, so don't keep polling.
+                self._end_ranked_follow(
+                    "No streamed Twitch channels found in that ranked match."
+                )
+                return
+            self._ranked_live_added = [
+                channel
+                for channel in self._ranked_live_channels
+                if channel not in self._manual_streams
+            ]
+            self._ranked_live_events = dict(match.events)
+            self._status_label.setText(
+                "Following ranked match: "
+                f"{', '.join(self._ranked_live_channels)}"
+            )
+            if self._ranked_live_added:
+                self._manual_streams.extend(self._ranked_live_added)
+                self._refresh_list()
+                self.manual_streams_changed.emit(list(self._manual_streams))
+                self._emit_active_streams()
+            return
+        previous = self._ranked_live_events
+        self._ranked_live_events = dict(match.events)
+        for channel, event in match.events.items():
+            if event == previous.get(channel):
+                continue
+            if "complete" in event or "forfeit" in event:
+                self._end_ranked_follow(f"{channel} finished the ranked match.")
+                return
+            if "reset" in event:
+                # Show whoever is still on their run.
+                others = [
+                    other
+                    for other in self._ranked_live_channels
+                    if other != channel
+                ]
+                if others:
+                    self.focus_channel(others[0])
+
+    def _on_ranked_live_error(self, message: str) -> None:
+        self._ranked_live_worker = None
+        if self._ranked_live_names:
+            self._status_label.setText(f"MCSR Ranked live lookup failed: {message}")
+
+    def _end_ranked_follow(self, message: str) -> None:
+        self._ranked_live_timer.stop()
+        self._ranked_live_names = []
+        self._ranked_live_events = {}
+        channels = self._ranked_live_channels
+        added = self._ranked_live_added
+        self._ranked_live_channels = []
+        self._ranked_live_added = []
+        if message:
+            self._status_label.setText(message)
+        if self._channel_key(self._focused_channel) in {
+            self._channel_key(channel) for channel in channels
+        }:
+            self._clear_focus()
+        removed = [channel for channel in added if channel in self._manual_streams]
+        if not removed:
+            return
+        for channel in removed:
+            self._manual_streams.remove(channel)
+        self._refresh_list()
+        self.manual_streams_changed.emit(list(self._manual_streams))
+        self._emit_active_streams()
+
+    def _move_stream(self, channel: str, offset: int) -> None:
+        if channel not in self._manual_streams:
+            return
+        index = self._manual_streams.index(channel)
+        target = index + offset
+        if target < 0 or target >= len(self._manual_streams):
+            return
+        self._manual_streams.insert(target, self._manual_streams.pop(index))
+        self._refresh_list()
+        self.manual_streams_changed.emit(list(self._manual_streams))
+        self._emit_active_streams()
+
     def _remove_stream(self, channel: str) -> None:
         if channel not in self._manual_streams:
             return
@@ -649,10 +991,32 @@ class ControlPanelWindow(QtWidgets.QWidget):
         if self._focused_channel == channel:
             self._focused_channel = None
             self._clear_focus_button.setEnabled(False)
+            self._report_focus_change("removed")
+        if self._channel_max_quality.pop(self._channel_key(channel), None):
+            # A re-added channel starts without its old @quality cap.
+            self._emit_settings()
         self._refresh_list()
         self.manual_streams_changed.emit(list(self._manual_streams))
         self._emit_active_streams()
 
+    def set_channel_live_states(
+        self,
+        requested: list[str],
+        live: list[str],
+    ) -> None:
+        live_keys = {self._channel_key(channel) for channel in live}
+        changed = False
+        for channel in requested:
+            channel_key = self._channel_key(channel)
+            if not channel_key:
+                continue
+            is_live = channel_key in live_keys
+            if self._channel_live.get(channel_key) != is_live:
+                self._channel_live[channel_key] = is_live
+                changed = True
+        if changed:
+            self._refresh_list()
+
     def _refresh_list(self) -> None:
         with perf_timer("control_panel.refresh_list"):
             self._manual_list.clear()
@@ -663,14 +1027,54 @@ class ControlPanelWindow(QtWidgets.QWidget):
             self._emit_overlay_info()
 
     def _refresh_manual_list(self) -> None:
-        for channel in self._manual_streams:
+        last_index = len(self._manual_streams) - 1
+        for index, channel in enumerate(self._manual_streams):
             item = QtWidgets.QListWidgetItem(self._manual_list)
             row_widget = QtWidgets.QWidget(self._manual_list)
             row_layout = QtWidgets.QHBoxLayout(row_widget)
             row_layout.setContentsMargins(6, 4, 6, 4)
-            label = QtWidgets.QLabel(channel, row_widget)
+            quality_cap = self._channel_max_quality.get(self._channel_key(channel))
+            label = QtWidgets.QLabel(
+                f"{channel}@{quality_cap}p" if quality_cap else channel,
+                row_widget,
+            )
             if channel == self._focused_channel:
                 label.setStyleSheet("font-weight: 600;")
+            live_state = self._channel_live.get(self._channel_key(channel))
+            status_label = QtWidgets.QLabel("", row_widget)
+            if live_state is True:
+                status_label.setText("live")
+                status_label.setStyleSheet("color: #2e9e4f;")
+            elif live_state is False:
+                status_label.setText("offline")
+                status_label.setStyleSheet("color: #888888;")
+            delay_input = QtWidgets.QSpinBox(row_widget)
+            delay_input.setRange(0, _MAX_VIDEO_DELAY_MS)
+            delay_input.setSingleStep(100)
+            delay_input.setSuffix(" ms")
+            delay_input.setToolTip("Extra video delay for this channel")
+            delay_input.setValue(
+                self._channel_video_delays_ms.get(self._channel_key(channel), 0)
+            )
+            delay_input.valueChanged.connect(
+                lambda value, c=channel: self._update_channel_video_delay(
+                    c, value
+                )
+            )
+            up_button = QtWidgets.QToolButton(row_widget)
+            up_button.setArrowType(QtCore.Qt.UpArrow)
+            up_button.setToolTip("Move up")
+            up_button.setEnabled(index > 0)
+            up_button.clicked.connect(
+                lambda _, c=channel: self._move_stream(c, -1)
+            )
+            down_button = QtWidgets.QToolButton(row_widget)
+            down_button.setArrowType(QtCore.Qt.DownArrow)
+            down_button.setToolTip("Move down")
+            down_button.setEnabled(index < last_index)
+            down_button.clicked.connect(
+                lambda _, c=channel: self._move_stream(c, 1)
+            )
             focus_button = QtWidgets.QPushButton("Focus", row_widget)
             focus_button.clicked.connect(
                 lambda _, c=channel: self._set_focus(c)
@@ -680,7 +1084,11 @@ class ControlPanelWindow(QtWidgets.QWidget):
                 lambda _, c=channel: self._remove_stream(c)
             )
             row_layout.addWidget(label)
+            row_layout.addWidget(status_label)
             row_layout.addStretch(1)
+            row_layout.addWidget(delay_input)
+            row_layout.addWidget(up_button)
+            row_layout.addWidget(down_button)
             row_layout.addWidget(focus_button)
             row_layout.addWidget(delete_button)
             item.setSizeHint(row_widget.sizeHint())
@@ -756,7 +1164,11 @@ class ControlPanelWindow(QtWidgets.QWidget):
             ]
         if self._hide_offline:
             visible = [run for run in visible if run.channel]
-        return visible
+        return [
+            run
+            for run in visible
+            if not run.channel or self._content_filter.allows(run.channel)
+        ]
 
     def _sorted_paceman_runs(self) -> list[PacemanRun]:
         visible_runs = self._filtered_paceman_runs()
@@ -820,14 +1232,23 @@ class ControlPanelWindow(QtWidgets.QWidget):
     def _maybe_auto_focus(self, visible_runs: list[PacemanRun]) -> None:
         if not self._paceman_mode or not self._pace_autofocus_enabled:
             return
+        visible_channels = {
+            self._channel_key(run.channel) for run in visible_runs if run.channel
+        }
+        if (
+            self._return_focus_channel is not None
+            and self._channel_key(self._return_focus_channel) in visible_channels
+        ):
+            # The manual pick is back, so hand focus back to it.
+            self._focused_channel = self._return_focus_channel
+            self._return_focus_channel = None
+            self._auto_focus_active = False
+            self._clear_focus_button.setEnabled(True)
+            self._report_focus_change("returned")
+            return
         if not self._pace_gate_met(visible_runs):
             return
         if self._focused_channel is not None and not self._auto_focus_active:
-            visible_channels = {
-                self._channel_key(run.channel)
-                for run in visible_runs
-                if run.channel
-            }
             if self._channel_key(self._focused_channel) in visible_channels:
                 return
         candidates = [
@@ -842,19 +1263,77 @@ class ControlPanelWindow(QtWidgets.QWidget):
                 self._focused_channel = None
                 self._auto_focus_active = False
                 self._clear_focus_button.setEnabled(False)
+                self._report_focus_change("pace_cleared")
             return
         best_run = min(candidates, key=lambda run: run.pace_score)
         if not best_run.channel:
             return
         if best_run.channel == self._focused_channel:
             return
+        if self._focused_channel is not None and not self._auto_focus_active:
+            self._return_focus_channel = self._focused_channel
         self._focused_channel = best_run.channel
         self._auto_focus_active = True
         self._clear_focus_button.setEnabled(True)
+        self._report_focus_change("pace")
         self._play_focus_bell()
 
+    def _load_lineup(self, lineup_file: str) -> None:
+        if lineup_file == self._lineup_file:
+            return
+        self._lineup_file = lineup_file
+        self._lineup_slots = []
+        if lineup_file:
+            try:
+                self._lineup_slots = load_lineup(Path(lineup_file).expanduser())
+            except (OSError, ValueError) as exc:
+                print(f"lineup load failed for '{lineup_file}': {exc}")
+        if self._lineup_slots:
+            self._lineup_timer.start()
+            # Deferred so the first switch happens once signals are connected.
+            QtCore.QTimer.singleShot(0, self._on_lineup_tick)
+        else:
+            self._lineup_timer.stop()
+        log_perf("control_panel.lineup_loaded", slots=len(self._lineup_slots))
+        self._update_lineup_label()
+
+    def _on_lineup_tick(self) -> None:
+        self._update_lineup_label()
+        self._emit_active_streams()
+
+    def upcoming_lineup_slot(self) -> LineupSlot | None:
+        return next_lineup_slot(self._lineup_slots)
+
+    def _lineup_channels(self) -> list[str]:
+        channels: list[str] = []
+        for slot in active_lineup_slots(self._lineup_slots):
+            if slot.channel not in channels:
+                channels.append(slot.channel)
+        return channels
+
+    def _update_lineup_label(self) -> None:
+        if not self._lineup_slots:
+            self._lineup_label.setVisible(False)
+            return
+        active = active_lineup_slots(self._lineup_slots)
+        upcoming = next_lineup_slot(self._lineup_slots)
+        if active:
+            text = "Lineup now: " + ", ".join(slot.runner for slot in active)
+        else:
+            text = "Lineup: nothing scheduled, showing the manual list"
+        if upcoming is not None:
+            text += (
+                f" (next: {upcoming.runner}"
+                f" at {upcoming.start.astimezone():%H:%M})"
+            )
+        elif not active:
+            text = "Lineup finished, showing the manual list"
+        self._lineup_label.setText(text)
+        self._lineup_label.setVisible(True)
+
     def _emit_active_streams(self) -> None:
-        manual_channels = list(self._manual_streams)
+        # A running lineup slot takes over from the hand-picked manual list.
+        manual_channels = self._lineup_channels() or list(self._manual_streams)
         paceman_channels: list[str] = []
         manual_layout = self.is_manual_source_active()
 
@@ -950,7 +1429,12 @@ class ControlPanelWindow(QtWidgets.QWidget):
                 "pace_paceman_enabled": self._pace_paceman_enabled,
                 "pace_paceman_threshold": self._pace_paceman_threshold,
                 "max_stream_quality": self._max_stream_quality,
+                "video_delay_ms": self._video_delay_ms,
+                "channel_video_delays_ms": dict(self._channel_video_delays_ms),
+                "channel_max_quality": dict(self._channel_max_quality),
                 "focus_bell_enabled": self._focus_bell_enabled,
+                "ad_mute_enabled": self._ad_mute_enabled,
+                "show_offline_placeholders": self._show_offline_placeholders,
                 "pace_good_splits": dict(self._pace_good_splits),
                 "pace_progression_bonus": dict(self._pace_progression_bonus),
             }
@@ -959,13 +1443,17 @@ class ControlPanelWindow(QtWidgets.QWidget):
     def _set_focus(self, channel: str | None, *, auto: bool = False) -> None:
         if not channel:
             return
+        if not auto:
+            self._return_focus_channel = None
         if not auto and channel == self._focused_channel:
             self._focused_channel = None
             self._auto_focus_active = False
+            self._report_focus_change("cleared")
         else:
             changed = channel != self._focused_channel
             self._focused_channel = channel
             self._auto_focus_active = auto
+            self._report_focus_change("pace" if auto else "manual")
             if changed:
                 self._play_focus_bell()
         self._clear_focus_button.setEnabled(self._focused_channel is not None)
@@ -977,12 +1465,39 @@ class ControlPanelWindow(QtWidgets.QWidget):
             return
         self._focused_channel = None
         self._auto_focus_active = False
+        self._return_focus_channel = None
         self._clear_focus_button.setEnabled(False)
+        self._report_focus_change("cleared")
         self._refresh_list()
         self._emit_active_streams()
 
+    def session_state(self) -> dict[str, object]:
+        return {
+            "focused_channel": self._focused_channel,
+            "auto_focus": self._auto_focus_active,
+        }
+
+    def restore_session_state(self, state: dict[str, object]) -> None:
+        channel = state.get("focused_channel")
+        if not isinstance(channel, str) or not channel.strip():
+            return
+        self._focused_channel = channel.strip()
+        self._auto_focus_active = bool(state.get("auto_focus", False))
+        self._clear_focus_button.setEnabled(True)
+        self._report_focus_change("restored")
+        self._refresh_list()
+        self._emit_active_streams()
+
+    def _report_focus_change(self, reason: str) -> None:
+        if self._focused_channel == self._reported_focus:
+            return
+        previous = self._reported_focus
+        self._reported_focus = self._focused_channel
+        self.focus_changed.emit(self._focused_channel, previous, reason)
+
     def _apply_settings(self, settings: dict[str, object]) -> None:
         self._applying_settings = True
+        self._content_filter = ContentFilter.from_settings(settings)
         event_setting = settings.get("paceman_event", "")
         if event_setting is None:
             self._paceman_event_slug = ""
@@ -1004,6 +1519,12 @@ class ControlPanelWindow(QtWidgets.QWidget):
             settings.get("focus_bell_enabled", False)
         )
         self._focus_bell_toggle.setChecked(self._focus_bell_enabled)
+        self._ad_mute_enabled = bool(settings.get("ad_mute_enabled", False))
+        self._ad_mute_toggle.setChecked(self._ad_mute_enabled)
+        self._show_offline_placeholders = bool(
+            settings.get("show_offline_placeholders", False)
+        )
+        self._offline_cards_toggle.setChecked(self._show_offline_placeholders)
         self._hide_offline = bool(
             settings.get("paceman_hide_offline", False)
         )
@@ -1056,18 +1577,37 @@ class ControlPanelWindow(QtWidgets.QWidget):
         self._pace_paceman_threshold_input.setEnabled(
             self._paceman_mode and self._pace_paceman_enabled
         )
-        quality_setting = settings.get("max_stream_quality", _QUALITY_STEPS[3])
+        quality_setting = settings.get("max_stream_quality", QUALITY_STEPS[3])
         try:
             quality_value = int(quality_setting)
         except (TypeError, ValueError):
-            quality_value = _QUALITY_STEPS[3]
-        if quality_value not in _QUALITY_STEPS:
+            quality_value = QUALITY_STEPS[3]
+        if quality_value not in QUALITY_STEPS:
             quality_value = min(
-                _QUALITY_STEPS, key=lambda step: abs(step - quality_value)
+                QUALITY_STEPS, key=lambda step: abs(step - quality_value)
             )
         self._max_stream_quality = quality_value
-        self._quality_slider.setValue(_QUALITY_STEPS.index(quality_value))
+        self._quality_slider.setValue(QUALITY_STEPS.index(quality_value))
         self._quality_value.setText(f"{quality_value}p")
+        try:
+            self._video_delay_ms = max(0, int(settings.get("video_delay_ms", 0)))
+        except (TypeError, ValueError):
+            self._video_delay_ms = 0
+        self._delay_input.setValue(self._video_delay_ms)
+        channel_delays = settings.get("channel_video_delays_ms")
+        if isinstance(channel_delays, dict):
+            self._channel_video_delays_ms = {
+                self._channel_key(channel): int(value)
+                for channel, value in channel_delays.items()
+                if self._channel_key(channel) and int(value) > 0
+            }
+        channel_qualities = settings.get("channel_max_quality")
+        if isinstance(channel_qualities, dict):
+            self._channel_max_quality = {
+                self._channel_key(channel): int(value)
+                for channel, value in channel_qualities.items()
+                if self._channel_key(channel) and int(value) > 0
+            }
         good_splits = settings.get("pace_good_splits")
         if isinstance(good_splits, dict):
             self._pace_good_splits = {
@@ -1087,6 +1627,7 @@ class ControlPanelWindow(QtWidgets.QWidget):
                 good_splits_sec=self._pace_good_splits,
                 progression_bonus=self._pace_progression_bonus,
             )
+        self._load_lineup(str(settings.get("lineup_file", "")))
         self._applying_settings = False
 
     def shutdown(self) -> None:
@@ -0,0 +1,61 @@
+from dataclasses import dataclass, fields, replace
+
+from PySide6 import QtGui
+
+OVERLAY_ANCHORS = ("top_left", "top_right", "bottom_left", "bottom_right")
+
+
+@dataclass(frozen=True)
+class OverlayTheme:
+    font_family: str = ""
+    font_size: int = 18
+    text_color: str = "#f5f5f5"
+    background_color: str = "#000000"
+    background_opacity: float = 0.6
+    corner_radius: int = 8
+    margin: int = 16
+    anchor: str = "top_left"
+    subtitle_font_family: str = ""
+    subtitle_color: str = "#f5f5f5"
+    subtitle_background_opacity: float = 0.67
+
+    @classmethod
+    def from_dict(cls, values: object) -> "OverlayTheme":
+        if not isinstance(values, dict):
+            return cls()
+        default = cls()
+        parsed: dict[str, object] = {}
+        for field in fields(cls):
+            if field.name not in values:
+                continue
+            fallback = getattr(default, field.name)
+            try:
+                parsed[field.name] = type(fallback)(values[field.name])
+            except (TypeError, ValueError):
+                continue
+        theme = cls(**parsed)
+        if theme.anchor not in OVERLAY_ANCHORS:
+            theme = replace(theme, anchor=default.anchor)
+        for name in ("text_color", "background_color", "subtitle_color"):
+            if not QtGui.QColor.isValidColorName(getattr(theme, name)):
+                theme = replace(theme, **{name: getattr(default, name)})
+        return theme
+
+    def background_rgba(self, opacity: float | None = None) -> QtGui.QColor:
+        color = QtGui.QColor(self.background_color)
+        alpha = self.background_opacity if opacity is None else opacity
+        color.setAlphaF(max(0.0, min(1.0, alpha)))
+        return color
+
+    def overlay_stylesheet(self, object_name: str) -> str:
+        background = self.background_rgba()
+        family = (
+            f" font-family: '{self.font_family}';" if self.font_family else ""
+        )
+        return (
+            f"QFrame#{object_name} {{ background: rgba({background.red()},"
+            f" {background.green()}, {background.blue()}, {background.alpha()});"
+            f" border-radius: {self.corner_radius}px; }}"
+            f" QFrame#{object_name} QLabel {{ color: {self.text_color};"
+            f" font-size: {self.font_size}px;{family} }}"
+        )
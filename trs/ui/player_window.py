@@ -1,21 +1,38 @@
+import ctypes
 import math
+import sys
 import time
 from pathlib import Path
 from dataclasses import dataclass
+from datetime import datetime, timezone
 
 from PySide6 import QtCore, QtGui, QtMultimedia, QtWidgets
 
-from ..config import APP_TITLE
+from ..config import APP_TITLE, DEFAULT_KEYBINDINGS
+from ..i18n import tr
 from ..perf_log import log_perf
-from ..stream_resolver import StreamEntry
+from ..stream_resolver import StreamEntry, StreamMetadata
+from ..test_source import TestPattern, is_test_source
+from .overlay_theme import OverlayTheme
 
 
 class _VideoSurface(QtWidgets.QWidget):
+    first_frame = QtCore.Signal()
+
     def __init__(self, parent: QtWidgets.QWidget | None = None) -> None:
         super().__init__(parent)
         self._image = QtGui.QImage()
+        self._received_frame = False
+        self._subtitle_text = ""
+        self._subtitles_enabled = False
+        self._subtitle_font_size = 20
+        self._theme = OverlayTheme()
+        self._crop: QtCore.QRectF | None = None
+        self._status_text = ""
+        self._key_color: QtGui.QColor | None = None
         self._sink = QtMultimedia.QVideoSink(self)
         self._sink.videoFrameChanged.connect(self._on_frame)
+        self._sink.subtitleTextChanged.connect(self._on_subtitle_text)
         self.setSizePolicy(
             QtWidgets.QSizePolicy.Expanding,
             QtWidgets.QSizePolicy.Expanding,
@@ -24,22 +41,129 @@ class _VideoSurface(QtWidgets.QWidget):
     def video_sink(self) -> QtMultimedia.QVideoSink:
         return self._sink
 
+    def detach(self) -> None:
+        self._sink.videoFrameChanged.disconnect(self._on_frame)
+        self._sink.subtitleTextChanged.disconnect(self._on_subtitle_text)
+        self._image = QtGui.QImage()
+
+    def set_subtitles(self, enabled: bool, font_size: int) -> None:
+        self._subtitles_enabled = enabled
+        self._subtitle_font_size = max(8, int(font_size))
+        self.update()
+
+    def _on_subtitle_text(self, text: str) -> None:
+        self._subtitle_text = text
+        if self._subtitles_enabled:
+            self.update()
+
     def _on_frame(self, frame: QtMultimedia.QVideoFrame) -> None:
         if not frame.isValid():
             return
-        image = frame.toImage()
+        self.present_image(frame.toImage())
+
+    def present_image(self, image: QtGui.QImage) -> None:
         if image.isNull():
             return
+        if self._status_text:
+            # Fresh video means whatever went wrong has recovered.
+            self.set_status_text("")
+        if not self._received_frame:
+            self._received_frame = True
+            self.first_frame.emit()
         self._image = image
         self.update()
 
     def paintEvent(self, event: QtGui.QPaintEvent) -> None:
         painter = QtGui.QPainter(self)
+        if self._key_color is not None:
+            painter.fillRect(self.rect(), self._key_color)
+            return
         painter.fillRect(self.rect(), QtCore.Qt.black)
-        if self._image.isNull():
+        if not self._image.isNull():
+            source = self._source_rect()
+            target = self._scaled_rect(source.size(), self.rect())
+            painter.drawImage(target, self._image, source)
+            if self._subtitles_enabled and self._subtitle_text:
+                self._paint_subtitle(painter, target)
+        if self._status_text:
+            self._paint_status(painter)
+
+    def set_key_color(self, color: QtGui.QColor | None) -> None:
+        self._key_color = color
+        self.update()
+
+    def set_status_text(self, text: str) -> None:
+        if text == self._status_text:
             return
-        target = self._scaled_rect(self._image.size(), self.rect())
-        painter.drawImage(target, self._image)
+        self._status_text = text
+        self.update()
+
+    def _paint_status(self, painter: QtGui.QPainter) -> None:
+        # Dim the frozen last frame so the card reads as an error state.
+        painter.fillRect(self.rect(), QtGui.QColor(0, 0, 0, 150))
+        font = painter.font()
+        if self._theme.font_family:
+            font.setFamily(self._theme.font_family)
+        font.setPixelSize(self._theme.font_size + 6)
+        font.setBold(True)
+        painter.setFont(font)
+        available = self.rect().adjusted(24, 24, -24, -24)
+        flags = QtCore.Qt.AlignCenter | QtCore.Qt.TextWordWrap
+        text_rect = painter.boundingRect(available, flags, self._status_text)
+        painter.fillRect(
+            text_rect.adjusted(-16, -10, 16, 10),
+            self._theme.background_rgba(),
+        )
+        painter.setPen(QtGui.QColor(self._theme.text_color))
+        painter.drawText(text_rect, flags, self._status_text)
+
+    def _paint_subtitle(
+        self,
+        painter: QtGui.QPainter,
+        video_rect: QtCore.QRect,
+    ) -> None:
+        font = painter.font()
+        if self._theme.subtitle_font_family:
+            font.setFamily(self._theme.subtitle_font_family)
+        font.setPixelSize(self._subtitle_font_size)
+        painter.setFont(font)
+        available = video_rect.adjusted(24, 0, -24, -16)
+        text_rect = painter.boundingRect(
+            available,
+            QtCore.Qt.AlignHCenter | QtCore.Qt.AlignBottom | QtCore.Qt.TextWordWrap,
+            self._subtitle_text,
+        )
+        background = text_rect.adjusted(-8, -4, 8, 4)
+        painter.fillRect(
+            background,
+            self._theme.background_rgba(self._theme.subtitle_background_opacity),
+        )
+        painter.setPen(QtGui.QColor(self._theme.subtitle_color))
+        painter.drawText(
+            text_rect,
+            QtCore.Qt.AlignHCenter | QtCore.Qt.TextWordWrap,
+            self._subtitle_text,
+        )
+
+    def set_crop(self, crop: QtCore.QRectF | None) -> None:
+        self._crop = crop
+        self.update()
+
+    def _source_rect(self) -> QtCore.QRect:
+        full = self._image.rect()
+        if self._crop is None:
+            return full
+        source = QtCore.QRect(
+            round(self._crop.x() * full.width()),
+            round(self._crop.y() * full.height()),
+            round(self._crop.width() * full.width()),
+            round(self._crop.height() * full.height()),
+        ).intersected(full)
+        return source if not source.isEmpty() else full
+
+    def set_theme(self, theme: OverlayTheme) -> None:
+        self._theme = theme
+        self.update()
 
     @staticmethod
     def _scaled_rect(
@@ -82,19 +206,40 @@ class _StreamContainer(QtWidgets.QWidget):
         layout = QtWidgets.QVBoxLayout(self)
         layout.setContentsMargins(0, 0, 0, 0)
         layout.addWidget(video_widget)
+        self._overlay_frame = overlay_frame
+        self._overlay_anchor = "top_left"
+        self._overlay_margin = 16
         overlay_frame.setParent(self)
         overlay_frame.raise_()
-        overlay_frame.move(16, 16)
+        self.position_overlay()
+
+    def set_overlay_position(self, anchor: str, margin: int) -> None:
+        self._overlay_anchor = anchor
+        self._overlay_margin = margin
+        self.position_overlay()
+
+    def position_overlay(self) -> None:
+        margin = self._overlay_margin
+        x = margin
+        y = margin
+        if self._overlay_anchor.endswith("right"):
+            x = self.width() - self._overlay_frame.width() - margin
+        if self._overlay_anchor.startswith("bottom"):
+            y = self.height() - self._overlay_frame.height() - margin
+        self._overlay_frame.move(max(0, x), max(0, y))
 
     def resizeEvent(self, event: QtGui.QResizeEvent) -> None:
         super().resizeEvent(event)
-        for child in self.findChildren(QtWidgets.QFrame):
-            if child.objectName() == "streamOverlay":
-                child.move(16, 16)
-                break
+        self.position_overlay()
 
 
 class PlayerWindow(QtWidgets.QMainWindow):
+    stream_error = QtCore.Signal(str, str)
+    subtitles_toggled = QtCore.Signal(bool)
+    marker_requested = QtCore.Signal(str)
+    playback_milestone = QtCore.Signal(str, str)
+    blanked_changed = QtCore.Signal(bool)
+
     def __init__(self) -> None:
         super().__init__()
         self.setWindowTitle(APP_TITLE)
@@ -118,17 +263,84 @@ class PlayerWindow(QtWidgets.QMainWindow):
         self._last_grid_cols = 0
         self._manual_grid_columns = 0
         self._manual_grid_rows = 0
+        self._fair_grid_fill = False
+        self._last_shown_at: dict[str, float] = {}
         self._overlay_info: dict[str, dict[str, str | None]] = {}
         self._overlay_enabled = True
+        self._overlay_theme = OverlayTheme()
+        self._stream_metadata: dict[str, StreamMetadata] = {}
         self._icon_cache: dict[str, QtGui.QPixmap] = {}
         self._channel_muted: dict[str, bool] = {}
+        self._host_window: QtGui.QWindow | None = None
+        self._host_timer: QtCore.QTimer | None = None
+        self._shortcuts: list[QtGui.QShortcut] = []
+        self._video_delay_ms = 0
+        self._channel_video_delays_ms: dict[str, int] = {}
+        self._applied_delay_ms: dict[str, int] = {}
+        self._delay_holds: set[str] = set()
+        self._channel_crops: dict[str, QtCore.QRectF] = {}
+        self._preferred_audio_track = ""
+        self._subtitles_enabled = False
+        self._subtitle_font_size = 20
+        self._ad_break_channels: set[str] = set()
+        self._reconnect_messages: dict[str, str] = {}
+        self._blanked = False
+        self._key_color: QtGui.QColor | None = None
+        self._blank_card = QtWidgets.QLabel(tr("card.blank"), self)
+        self._blank_card.setAlignment(QtCore.Qt.AlignCenter)
+        self._blank_card.setVisible(False)
+        self._duck_key: QtCore.QKeyCombination | None = None
+        self._duck_volume = 0.25
+        self._ducked = False
+        self._last_offline_channels: list[str] = []
+        self._offline_cards: list[QtWidgets.QLabel] = []
+        self._startup_timeout_sec = 20.0
+        self._waiting_since: dict[str, float] = {}
+        self._watchdog = QtCore.QTimer(self)
+        self._watchdog.setInterval(2_000)
+        self._watchdog.timeout.connect(self._check_stalled_players)
+        self._watchdog.start()
         self._icon_dir = (
             Path(__file__).resolve().parent.parent
             / "assets"
             / "paceman-icons"
         )
+        self.set_keybindings({})
+
+    def embed_into(self, win_id: int) -> bool:
+        if not _native_window_exists(win_id):
+            return False
+        host = QtGui.QWindow.fromWinId(win_id)
+        # fromWinId wraps any handle; a dead one has no geometry.
+        if host is None or host.geometry().isEmpty():
+            return False
+        self.setWindowFlags(QtCore.Qt.FramelessWindowHint)
+        self._grid.setContentsMargins(0, 0, 0, 0)
+        self.winId()
+        handle = self.windowHandle()
+        if handle is None:
+            return False
+        handle.setParent(host)
+        self._host_window = host
+        # Foreign windows don't deliver resize events, so track the host size.
+        self._host_timer = QtCore.QTimer(self)
+        self._host_timer.setInterval(250)
+        self._host_timer.timeout.connect(self._follow_host_size)
+        self._host_timer.start()
+        self._follow_host_size()
+        log_perf("player.embedded", win_id=win_id)
+        return True
+
+    def _follow_host_size(self) -> None:
+        if self._host_window is None:
+            return
+        size = self._host_window.size()
+        if size.isValid() and size != self.size():
+            self.setGeometry(0, 0, size.width(), size.height())
 
     def set_fullscreen(self, enabled: bool) -> None:
+        if self._host_window is not None:
+            return
         if enabled == self.isFullScreen():
             return
         if enabled:
@@ -138,54 +350,364 @@ class PlayerWindow(QtWidgets.QMainWindow):
             self.showNormal()
             self._grid.setContentsMargins(*self._normal_margins)
 
+    def set_keybindings(self, overrides: dict[str, object]) -> None:
+        actions = {
+            "toggle_fullscreen": lambda: self.set_fullscreen(
+                not self.isFullScreen()
+            ),
+            "exit_fullscreen": lambda: self.set_fullscreen(False),
+            "cycle_audio_track": self._cycle_audio_tracks,
+            "toggle_subtitles": self._toggle_subtitles,
+            "add_marker": lambda: self._request_marker(False),
+            "add_marker_with_note": lambda: self._request_marker(True),
+            "toggle_blank": lambda: self.set_blanked(not self._blanked),
+        }
+        for shortcut in self._shortcuts:
+            shortcut.setEnabled(False)
+            shortcut.deleteLater()
+        self._shortcuts = []
+        self._duck_key = None
+        bound: dict[str, str] = {}
+        for action, default in DEFAULT_KEYBINDINGS.items():
+            binding = overrides.get(action, default)
+            sequence_text = "" if binding is None else str(binding).strip()
+            if not sequence_text:
+                continue
+            # Comma-separated sequences are chords, e.g. "Ctrl+K, M".
+            sequence = QtGui.QKeySequence(sequence_text)
+            if sequence.isEmpty():
+                print(f"invalid key binding for '{action}': {sequence_text}")
+                sequence = QtGui.QKeySequence(default)
+            if action == "push_to_duck" and sequence.count() > 1:
+                print(f"push_to_duck cannot be a chord: {sequence_text}")
+                sequence = QtGui.QKeySequence(default)
+            # Check the sequence that is actually bound, fallbacks included.
+            portable = sequence.toString()
+            if portable in bound:
+                print(
+                    f"key binding {portable} for '{action}' is already used"
+                    f" by '{bound[portable]}'"
+                )
+                continue
+            bound[portable] = action
+            if action == "push_to_duck":
+                # Held rather than triggered, so it is handled in keyPressEvent.
+                self._duck_key = sequence[0]
+                continue
+            shortcut = QtGui.QShortcut(sequence, self)
+            shortcut.activated.connect(actions[action])
+            self._shortcuts.append(shortcut)
+        for action in overrides:
+            if action not in DEFAULT_KEYBINDINGS:
+                print(f"unknown key binding action '{action}'")
+
     def keyPressEvent(self, event: QtGui.QKeyEvent) -> None:
-        if event.key() == QtCore.Qt.Key_F11:
-            self.set_fullscreen(not self.isFullScreen())
-            event.accept()
-            return
-        if event.key() == QtCore.Qt.Key_Escape and self.isFullScreen():
-            self.set_fullscreen(False)
-            event.accept()
+        if self._duck_key is not None and event.keyCombination() == self._duck_key:
+            if not event.isAutoRepeat():
+                self._set_ducked(True)
             return
         super().keyPressEvent(event)
 
+    def keyReleaseEvent(self, event: QtGui.QKeyEvent) -> None:
+        # Modifiers may already be up on release, so only compare the key.
+        if self._duck_key is not None and event.key() == self._duck_key.key():
+            if not event.isAutoRepeat():
+                self._set_ducked(False)
+            return
+        super().keyReleaseEvent(event)
+
+    def changeEvent(self, event: QtCore.QEvent) -> None:
+        if event.type() == QtCore.QEvent.ActivationChange and not self.isActiveWindow():
+            # The release would never arrive while another window has focus.
+            self._set_ducked(False)
+        super().changeEvent(event)
+
+    def resizeEvent(self, event: QtGui.QResizeEvent) -> None:
+        super().resizeEvent(event)
+        self._blank_card.setGeometry(self.rect())
+
+    def set_blanked(self, blanked: bool) -> None:
+        if blanked == self._blanked:
+            return
+        self._blanked = blanked
+        if blanked:
+            # A neutral card, deliberately without channel names or overlays.
+            self._blank_card.setStyleSheet(
+                f"background-color: {self._overlay_theme.background_color};"
+                f" color: {self._overlay_theme.text_color};"
+                f" font-size: {self._overlay_theme.font_size + 12}px;"
+                " font-weight: bold;"
+            )
+            self._blank_card.setGeometry(self.rect())
+            self._blank_card.raise_()
+        self._blank_card.setVisible(blanked)
+        self._apply_audio_levels(list(self._entries.values()))
+        log_perf("player_window.blanked", active=blanked)
+        self.blanked_changed.emit(blanked)
+
+    def set_overlay_only(self, color: str) -> bool:
+        key_color = QtGui.QColor(color)
+        if not key_color.isValid():
+            print(f"invalid overlay-only key color '{color}'")
+            return False
+        # Tiles and gaps share one flat color so OBS can key the whole window.
+        self._key_color = key_color
+        self._central.setAutoFillBackground(True)
+        palette = self._central.palette()
+        palette.setColor(QtGui.QPalette.Window, key_color)
+        self._central.setPalette(palette)
+        for entry in self._entries.values():
+            entry.video_widget.set_key_color(key_color)
+            entry.player.stop()
+            entry.audio_output.setVolume(0.0)
+        return True
+
+    def is_blanked(self) -> bool:
+        return self._blanked
+
+    def set_duck_volume(self, volume: float) -> None:
+        self._duck_volume = max(0.0, min(1.0, float(volume)))
+        if self._ducked:
+            self._apply_audio_levels(list(self._entries.values()))
+
+    def _set_ducked(self, ducked: bool) -> None:
+        if ducked == self._ducked:
+            return
+        self._ducked = ducked
+        log_perf("player_window.ducked", active=ducked)
+        self._apply_audio_levels(list(self._entries.values()))
+
+    def _toggle_subtitles(self) -> None:
+        self.set_subtitles(not self._subtitles_enabled)
+        self.subtitles_toggled.emit(self._subtitles_enabled)
+
+    def set_subtitles(
+        self,
+        enabled: bool,
+        font_size: int | None = None,
+    ) -> None:
+        self._subtitles_enabled = enabled
+        if font_size is not None:
+            self._subtitle_font_size = max(8, int(font_size))
+        for entry in self._entries.values():
+            self._apply_subtitle_track(entry.player)
+            entry.video_widget.set_subtitles(
+                self._subtitles_enabled,
+                self._subtitle_font_size,
+            )
+
+    def _apply_subtitle_track(self, player: QtMultimedia.QMediaPlayer) -> None:
+        if not self._subtitles_enabled:
+            player.setActiveSubtitleTrack(-1)
+            return
+        if player.subtitleTracks() and player.activeSubtitleTrack() < 0:
+            player.setActiveSubtitleTrack(0)
+
+    def set_preferred_audio_track(self, preferred: str) -> None:
+        self._preferred_audio_track = preferred.strip().lower()
+        for entry in self._entries.values():
+            self._apply_preferred_audio_track(entry.player)
+
+    def _apply_preferred_audio_track(
+        self,
+        player: QtMultimedia.QMediaPlayer,
+    ) -> None:
+        if not self._preferred_audio_track:
+            return
+        for index, track in enumerate(player.audioTracks()):
+            if self._preferred_audio_track in _audio_track_label(track).lower():
+                if player.activeAudioTrack() != index:
+                    player.setActiveAudioTrack(index)
+                return
+
+    def _request_marker(self, with_note: bool) -> None:
+        note = ""
+        if with_note:
+            note, accepted = QtWidgets.QInputDialog.getText(
+                self,
+                tr("marker.dialog_title"),
+                tr("marker.note_label"),
+            )
+            if not accepted:
+                return
+        self.marker_requested.emit(note.strip())
+
+    def _cycle_audio_tracks(self) -> None:
+        for entry in self._entries.values():
+            if self._channel_muted.get(entry.channel, True):
+                continue
+            tracks = entry.player.audioTracks()
+            if len(tracks) < 2:
+                continue
+            next_index = (entry.player.activeAudioTrack() + 1) % len(tracks)
+            entry.player.setActiveAudioTrack(next_index)
+            label = _audio_track_label(tracks[next_index])
+            log_perf(
+                "player_window.audio_track",
+                channel=entry.channel,
+                index=next_index,
+                label=label,
+            )
+            print(f"audio track for '{entry.channel}': {label}")
+
+    def set_fair_grid_fill(self, enabled: bool) -> None:
+        self._fair_grid_fill = bool(enabled)
+
+    def _fair_selection(
+        self,
+        streams: list[StreamEntry],
+        capacity: int,
+    ) -> list[StreamEntry]:
+        # Shown channels keep their slot; free slots go to whoever waited longest.
+        shown = {stream.channel for stream in self._last_streams}
+        kept = [stream for stream in streams if stream.channel in shown][:capacity]
+        waiting = sorted(
+            (self._last_shown_at.get(stream.channel, float("-inf")), index)
+            for index, stream in enumerate(streams)
+            if stream.channel not in shown
+        )
+        chosen = {stream.channel for stream in kept}
+        for _shown_at, index in waiting[: capacity - len(kept)]:
+            chosen.add(streams[index].channel)
+        return [stream for stream in streams if stream.channel in chosen]
+
     def set_manual_grid_limits(self, columns: int, rows: int) -> None:
         self._manual_grid_columns = max(0, int(columns))
         self._manual_grid_rows = max(0, int(rows))
 
+    def set_video_delays(
+        self,
+        delay_ms: int,
+        channel_delays_ms: dict[str, int],
+    ) -> None:
+        self._video_delay_ms = max(0, int(delay_ms))
+        self._channel_video_delays_ms = {
+            str(channel).strip().lower(): max(0, int(value))
+            for channel, value in channel_delays_ms.items()
+        }
+        for entry in self._entries.values():
+            self._sync_delay(entry)
+
+    def set_channel_crops(self, crops: dict[str, object]) -> None:
+        self._channel_crops = {}
+        for channel, values in crops.items():
+            crop = _parse_crop(values)
+            if crop is None:
+                print(f"invalid crop for '{channel}': {values}")
+                continue
+            self._channel_crops[str(channel).strip().lower()] = crop
+        for entry in self._entries.values():
+            entry.video_widget.set_crop(self._crop_for_channel(entry.channel))
+
+    def _crop_for_channel(self, channel: str) -> QtCore.QRectF | None:
+        return self._channel_crops.get(channel.strip().lower())
+
+    def _sync_delay(self, entry: "_PlayerEntry") -> None:
+        # Pausing the player delays audio and video together, and the backlog
+        # stays in the player's own segment buffer instead of decoded frames.
+        channel = entry.channel
+        if channel in self._delay_holds or entry.player.source().isEmpty():
+            return
+        target = self._delay_for_channel(channel)
+        applied = self._applied_delay_ms.get(channel, 0)
+        if target < applied:
+            # A live stream can't skip ahead in its buffer; reopen at the live
+            # edge and hold for the full delay once it has buffered again.
+            self._applied_delay_ms[channel] = 0
+            entry.player.stop()
+            entry.player.play()
+            return
+        if target == applied or entry.player.mediaStatus() not in (
+            QtMultimedia.QMediaPlayer.LoadedMedia,
+            QtMultimedia.QMediaPlayer.BufferedMedia,
+        ):
+            return
+        extra = target - applied
+        self._delay_holds.add(channel)
+        entry.player.pause()
+        QtCore.QTimer.singleShot(
+            extra,
+            lambda e=entry, ms=extra: self._end_delay_hold(e, ms),
+        )
+
+    def _end_delay_hold(self, entry: "_PlayerEntry", held_ms: int) -> None:
+        if self._entries.get(entry.channel) is not entry:
+            return
+        self._delay_holds.discard(entry.channel)
+        self._applied_delay_ms[entry.channel] = (
+            self._applied_delay_ms.get(entry.channel, 0) + held_ms
+        )
+        entry.player.play()
+        self._sync_delay(entry)
+
+    def _delay_for_channel(self, channel: str) -> int:
+        channel_delay = self._channel_video_delays_ms.get(
+            channel.strip().lower(), 0
+        )
+        return self._video_delay_ms + channel_delay
+
     def set_streams(
         self,
         streams: list[StreamEntry],
         focused: bool = False,
         manual_mode: bool = False,
+        offline_channels: list[str] | None = None,
     ) -> None:
         start = time.perf_counter()
         effective_streams = list(streams)
+        effective_offline = [] if focused else list(offline_channels or [])
         if (
             manual_mode
             and not focused
             and self._manual_grid_columns > 0
             and self._manual_grid_rows > 0
         ):
-            effective_streams = effective_streams[
-                : self._manual_grid_columns * self._manual_grid_rows
+            grid_capacity = self._manual_grid_columns * self._manual_grid_rows
+            if self._fair_grid_fill and len(effective_streams) > grid_capacity:
+                effective_streams = self._fair_selection(
+                    effective_streams,
+                    grid_capacity,
+                )
+            else:
+                effective_streams = effective_streams[:grid_capacity]
+            effective_offline = effective_offline[
+                : max(0, grid_capacity - len(effective_streams))
             ]
         if (
             effective_streams == self._last_streams
+            and effective_offline == self._last_offline_channels
             and focused == self._last_focused
             and manual_mode == self._last_manual_mode
             and self._manual_grid_columns == self._last_manual_grid_columns
             and self._manual_grid_rows == self._last_manual_grid_rows
         ):
             return
+        now = time.monotonic()
+        for stream in self._last_streams:
+            self._last_shown_at[stream.channel] = now
         self._last_streams = list(effective_streams)
+        self._last_offline_channels = list(effective_offline)
         self._last_focused = focused
         self._last_manual_mode = manual_mode
         self._last_manual_grid_columns = self._manual_grid_columns
         self._last_manual_grid_rows = self._manual_grid_rows
+        self._clear_offline_cards()
+        if not effective_streams and effective_offline:
+            self._clear_players()
+            columns = self._compute_grid_columns(
+                len(effective_offline),
+                manual_mode=manual_mode,
+            )
+            self._add_offline_cards(effective_offline, 0, columns)
+            self._apply_grid_stretch(
+                math.ceil(len(effective_offline) / columns),
+                columns,
+                False,
+            )
+            return
         if not effective_streams:
             self._clear_players()
-            self._placeholder = QtWidgets.QLabel("No streams configured.", self)
+            self._placeholder = QtWidgets.QLabel(tr("player.no_streams"), self)
             self._placeholder.setAlignment(QtCore.Qt.AlignCenter)
             self._grid.addWidget(self._placeholder, 0, 0)
             return
@@ -200,6 +722,7 @@ class PlayerWindow(QtWidgets.QMainWindow):
         active_channels: set[str] = set()
         created = 0
         reused = 0
+        rebuilt = 0
         for index, stream in enumerate(effective_streams):
             active_channels.add(stream.channel)
             if stream.channel not in self._channel_muted:
@@ -207,7 +730,9 @@ class PlayerWindow(QtWidgets.QMainWindow):
             entry = old_entries.pop(stream.channel, None)
             if entry is None or entry.url != stream.url:
                 if entry is not None:
+                    # A new URL gets a fresh player instead of a re-pointed one.
                     self._release_entry(entry)
+                    rebuilt += 1
                 entry = self._create_entry(stream.channel, stream.url)
                 created += 1
             else:
@@ -221,6 +746,7 @@ class PlayerWindow(QtWidgets.QMainWindow):
         for channel in list(self._channel_muted):
             if channel not in active_channels:
                 self._channel_muted.pop(channel, None)
+        self._ad_break_channels &= active_channels
 
         self._clear_layout(ordered_entries)
         use_focused_layout = (
@@ -230,20 +756,27 @@ class PlayerWindow(QtWidgets.QMainWindow):
         if use_focused_layout:
             rows, cols = self._layout_focused(ordered_entries)
         else:
+            tile_count = len(ordered_entries) + len(effective_offline)
             columns = self._compute_grid_columns(
-                len(ordered_entries),
+                tile_count,
                 manual_mode=manual_mode,
             )
             for index, entry in enumerate(ordered_entries):
                 row = index // columns
                 col = index % columns
                 self._add_player_widget(row, col, entry)
-            rows = math.ceil(len(ordered_entries) / columns)
+            self._add_offline_cards(
+                effective_offline,
+                len(ordered_entries),
+                columns,
+            )
+            rows = math.ceil(tile_count / columns)
             cols = columns
         self._apply_grid_stretch(rows, cols, focused)
         self._apply_audio_levels(ordered_entries)
         for entry in ordered_entries:
             self._update_entry_overlay(entry)
+        self._update_window_title()
         duration_ms = (time.perf_counter() - start) * 1000.0
         log_perf(
             "player_window.set_streams.internal",
@@ -252,9 +785,48 @@ class PlayerWindow(QtWidgets.QMainWindow):
             focused=focused,
             created=created,
             reused=reused,
+            rebuilt=rebuilt,
             released=released,
         )
 
+    def channel_mute_states(self) -> dict[str, bool]:
+        return dict(self._channel_muted)
+
+    def restore_channel_mute_states(self, muted: dict[str, bool]) -> None:
+        for channel, is_muted in muted.items():
+            self._channel_muted[str(channel)] = bool(is_muted)
+
+    def displayed_channels(self) -> list[str]:
+        return [stream.channel for stream in self._last_streams]
+
+    def set_overlay_theme(self, theme: OverlayTheme) -> None:
+        if theme == self._overlay_theme:
+            return
+        self._overlay_theme = theme
+        for entry in self._entries.values():
+            entry.video_widget.set_theme(theme)
+            entry.overlay_frame.setStyleSheet(
+                theme.overlay_stylesheet("streamOverlay")
+            )
+            entry.container.set_overlay_position(theme.anchor, theme.margin)
+            self._update_entry_overlay(entry)
+
+    def set_stream_metadata(self, metadata: dict[str, StreamMetadata]) -> None:
+        if metadata == self._stream_metadata:
+            return
+        self._stream_metadata = dict(metadata)
+        for entry in self._entries.values():
+            self._update_entry_overlay(entry)
+        self._update_window_title()
+
+    def _update_window_title(self) -> None:
+        primary = self._last_streams[0].channel if self._last_streams else None
+        metadata = self._stream_metadata.get(primary) if primary else None
+        if primary is None or metadata is None or not metadata.title:
+            self.setWindowTitle(APP_TITLE)
+            return
+        self.setWindowTitle(f"{APP_TITLE} - {primary}: {metadata.title}")
+
     def set_overlay_info(
         self,
         info: dict[str, dict[str, str | None]],
@@ -279,6 +851,32 @@ class PlayerWindow(QtWidgets.QMainWindow):
     ) -> None:
         self._grid.addWidget(entry.container, row, col, 1, col_span)
 
+    def _add_offline_cards(
+        self,
+        channels: list[str],
+        first_index: int,
+        columns: int,
+    ) -> None:
+        for offset, channel in enumerate(channels):
+            index = first_index + offset
+            card = QtWidgets.QLabel(tr("card.offline", channel=channel), self)
+            card.setAlignment(QtCore.Qt.AlignCenter)
+            card.setMinimumSize(160, 90)
+            card.setStyleSheet(
+                f"background-color: {self._overlay_theme.background_color};"
+                f" color: {self._overlay_theme.text_color};"
+                f" font-size: {self._overlay_theme.font_size}px;"
+                " font-weight: bold;"
+            )
+            self._grid.addWidget(card, index // columns, index % columns)
+            self._offline_cards.append(card)
+
+    def _clear_offline_cards(self) -> None:
+        for card in self._offline_cards:
+            self._grid.removeWidget(card)
+            card.deleteLater()
+        self._offline_cards.clear()
+
     def _clear_players(self) -> None:
         for entry in self._entries.values():
             self._release_entry(entry)
@@ -286,19 +884,71 @@ class PlayerWindow(QtWidgets.QMainWindow):
             self._grid.removeWidget(self._placeholder)
             self._placeholder.deleteLater()
             self._placeholder = None
+        self._clear_offline_cards()
 
         self._entries.clear()
         self._apply_grid_stretch(0, 0, False)
 
     def shutdown(self) -> None:
+        self._watchdog.stop()
         self._clear_players()
 
     def _on_error(
-        self, error: QtMultimedia.QMediaPlayer.Error, error_string: str
+        self,
+        channel: str,
+        error: QtMultimedia.QMediaPlayer.Error,
+        error_string: str,
     ) -> None:
         if error == QtMultimedia.QMediaPlayer.NoError:
             return
-        print(f"qt multimedia error: {error_string}")
+        print(f"qt multimedia error for '{channel}': {error_string}")
+        self.stream_error.emit(channel, error_string)
+
+    def set_startup_timeout(self, seconds: float) -> None:
+        self._startup_timeout_sec = max(0.0, float(seconds))
+
+    def _on_media_status(
+        self,
+        channel: str,
+        player: QtMultimedia.QMediaPlayer,
+        status: QtMultimedia.QMediaPlayer.MediaStatus,
+    ) -> None:
+        entry = self._entries.get(channel)
+        if entry is None or entry.player is not player:
+            # A released player can still report while it winds down.
+            return
+        if status in (
+            QtMultimedia.QMediaPlayer.LoadedMedia,
+            QtMultimedia.QMediaPlayer.BufferedMedia,
+        ):
+            self._waiting_since.pop(channel, None)
+            self.playback_milestone.emit(channel, "media_loaded")
+            self._sync_delay(entry)
+        elif status in (
+            QtMultimedia.QMediaPlayer.LoadingMedia,
+            QtMultimedia.QMediaPlayer.StalledMedia,
+            QtMultimedia.QMediaPlayer.BufferingMedia,
+        ):
+            self._waiting_since.setdefault(channel, time.monotonic())
+
+    def _check_stalled_players(self) -> None:
+        if self._startup_timeout_sec <= 0:
+            return
+        now = time.monotonic()
+        for channel, since in list(self._waiting_since.items()):
+            if now - since < self._startup_timeout_sec:
+                continue
+            self._waiting_since.pop(channel, None)
+            if channel not in self._entries:
+                continue
+            log_perf(
+                "player_window.stalled",
+                channel=channel,
+                waited_sec=round(now - since, 1),
+            )
+            message = f"no playback after {self._startup_timeout_sec:.0f}s"
+            print(f"qt multimedia stall for '{channel}': {message}")
+            self.stream_error.emit(channel, message)
 
     def _clear_layout(self, entries: list["_PlayerEntry"]) -> None:
         for entry in entries:
@@ -306,8 +956,61 @@ class PlayerWindow(QtWidgets.QMainWindow):
 
     def _apply_audio_levels(self, entries: list["_PlayerEntry"]) -> None:
         for entry in entries:
-            is_muted = self._channel_muted.get(entry.channel, True)
-            entry.audio_output.setVolume(0.0 if is_muted else 1.0)
+            entry.audio_output.setVolume(self._volume_for_channel(entry.channel))
+
+    def _volume_for_channel(self, channel: str) -> float:
+        if self._channel_muted.get(channel, True):
+            return 0.0
+        if channel in self._ad_break_channels or self._blanked:
+            return 0.0
+        return self._duck_volume if self._ducked else 1.0
+
+    def has_audible_stream(self) -> bool:
+        return any(
+            self._volume_for_channel(channel) > 0.0 for channel in self._entries
+        )
+
+    def audible_streams(self) -> list[StreamEntry]:
+        return [
+            StreamEntry(channel=entry.channel, url=entry.url)
+            for entry in self._entries.values()
+            if not self._channel_muted.get(entry.channel, True)
+        ]
+
+    def set_reconnecting(self, channel: str, attempt: int) -> None:
+        if attempt > 0:
+            message = tr("card.reconnecting", channel=channel, attempt=attempt)
+            self._reconnect_messages[channel] = message
+        else:
+            message = ""
+            self._reconnect_messages.pop(channel, None)
+        entry = self._entries.get(channel)
+        if entry is not None:
+            entry.video_widget.set_status_text(message)
+
+    def set_ad_break(self, channel: str, url: str, active: bool) -> None:
+        entry = self._entries.get(channel)
+        if entry is None or entry.url != url:
+            return
+        if active == (channel in self._ad_break_channels):
+            return
+        if active:
+            self._ad_break_channels.add(channel)
+        else:
+            self._ad_break_channels.discard(channel)
+        log_perf("player_window.ad_break", channel=channel, active=active)
+        entry.audio_output.setVolume(self._volume_for_channel(channel))
+        self._update_entry_overlay(entry)
+
+    def clear_ad_breaks(self) -> None:
+        channels = list(self._ad_break_channels)
+        self._ad_break_channels.clear()
+        for channel in channels:
+            entry = self._entries.get(channel)
+            if entry is None:
+                continue
+            entry.audio_output.setVolume(self._volume_for_channel(channel))
+            self._update_entry_overlay(entry)
 
     def _apply_grid_stretch(self, rows: int, cols: int, focused: bool) -> None:
         for row in range(rows, self._last_grid_rows):
@@ -339,19 +1042,53 @@ class PlayerWindow(QtWidgets.QMainWindow):
 
     def _create_entry(self, channel: str, url: str) -> "_PlayerEntry":
         video_widget = _VideoSurface(self)
+        video_widget.set_theme(self._overlay_theme)
+        video_widget.set_crop(self._crop_for_channel(channel))
+        video_widget.set_subtitles(
+            self._subtitles_enabled,
+            self._subtitle_font_size,
+        )
         player = QtMultimedia.QMediaPlayer(self)
         audio_output = QtMultimedia.QAudioOutput(self)
         player.setAudioOutput(audio_output)
         player.setVideoOutput(video_widget.video_sink())
-        player.errorOccurred.connect(self._on_error)
-        player.setSource(QtCore.QUrl(url))
-        player.play()
+        player.errorOccurred.connect(
+            lambda error, message, c=channel: self._on_error(c, error, message)
+        )
+        player.tracksChanged.connect(
+            lambda p=player: self._apply_preferred_audio_track(p)
+        )
+        player.tracksChanged.connect(
+            lambda p=player: self._apply_subtitle_track(p)
+        )
+        player.mediaStatusChanged.connect(
+            lambda status, c=channel, p=player: self._on_media_status(
+                c, p, status
+            )
+        )
+        video_widget.set_status_text(self._reconnect_messages.get(channel, ""))
+        video_widget.first_frame.connect(
+            lambda c=channel: self._reconnect_messages.pop(c, None)
+        )
+        video_widget.first_frame.connect(
+            lambda c=channel: self.playback_milestone.emit(c, "first_frame")
+        )
+        if self._key_color is not None:
+            # Overlay-only output needs the live status, not the video.
+            video_widget.set_key_color(self._key_color)
+        elif is_test_source(url):
+            # Owned by the surface, so it goes away with the tile.
+            pattern = TestPattern(channel, video_widget)
+            pattern.frame_ready.connect(video_widget.present_image)
+        else:
+            player.setSource(QtCore.QUrl(url))
+            player.play()
+            self._waiting_since[channel] = time.monotonic()
+        self.playback_milestone.emit(channel, "player_created")
         overlay_frame = _ClickableOverlay(self)
         overlay_frame.setObjectName("streamOverlay")
         overlay_frame.setStyleSheet(
-            "QFrame#streamOverlay { background: rgba(0, 0, 0, 150);"
-            " border-radius: 8px; }"
-            " QFrame#streamOverlay QLabel { color: #f5f5f5; font-size: 18px; }"
+            self._overlay_theme.overlay_stylesheet("streamOverlay")
         )
         overlay_frame.clicked.connect(
             lambda c=channel: self._toggle_channel_mute(c)
@@ -378,7 +1115,15 @@ class PlayerWindow(QtWidgets.QMainWindow):
         name_label.setFont(font)
         name_label.setAttribute(QtCore.Qt.WA_TransparentForMouseEvents, True)
         overlay_layout.addWidget(name_label)
+        meta_label = QtWidgets.QLabel("", overlay_frame)
+        meta_label.setAttribute(QtCore.Qt.WA_TransparentForMouseEvents, True)
+        meta_label.setVisible(False)
+        overlay_layout.addWidget(meta_label)
         container = _StreamContainer(video_widget, overlay_frame, self)
+        container.set_overlay_position(
+            self._overlay_theme.anchor,
+            self._overlay_theme.margin,
+        )
         overlay_frame.setVisible(False)
         return _PlayerEntry(
             channel=channel,
@@ -391,13 +1136,21 @@ class PlayerWindow(QtWidgets.QMainWindow):
             overlay_icon=icon_label,
             overlay_time=time_label,
             overlay_name=name_label,
+            overlay_meta=meta_label,
         )
 
     def _release_entry(self, entry: "_PlayerEntry") -> None:
+        self._waiting_since.pop(entry.channel, None)
+        self._applied_delay_ms.pop(entry.channel, None)
+        self._delay_holds.discard(entry.channel)
+        # Fixed teardown order, so nothing from the old stream can reach the
+        # player that replaces it or keep its decoder and network alive.
+        entry.player.blockSignals(True)
         entry.player.stop()
+        entry.player.setSource(QtCore.QUrl())
         entry.player.setVideoOutput(None)
         entry.player.setAudioOutput(None)
-        entry.player.setSource(QtCore.QUrl())
+        entry.video_widget.detach()
         entry.player.deleteLater()
         entry.audio_output.deleteLater()
         self._grid.removeWidget(entry.container)
@@ -413,20 +1166,29 @@ class PlayerWindow(QtWidgets.QMainWindow):
         split_time = info.get("split_time") or ""
         pb_time = info.get("pb_time") or ""
         name_label = (
-            f"{name} (PB: {pb_time})" if pb_time else name
+            tr("overlay.personal_best", name=name, pb_time=pb_time)
+            if pb_time
+            else name
         )
         entry.overlay_name.setText(name_label)
-        mute_state = "Muted" if is_muted else "Live"
+        if entry.channel in self._ad_break_channels:
+            mute_state = tr("overlay.ad_break")
+        else:
+            mute_state = tr("overlay.muted") if is_muted else tr("overlay.live")
         if split_time:
             entry.overlay_time.setText(f"{split_time} • {mute_state}")
         else:
             entry.overlay_time.setText(mute_state)
+        meta_text = _metadata_label(self._stream_metadata.get(entry.channel))
+        entry.overlay_meta.setText(meta_text)
+        entry.overlay_meta.setVisible(bool(meta_text))
         icon_name = info.get("icon_name")
         pixmap = self._pixmap_for_icon(icon_name)
         entry.overlay_icon.setPixmap(pixmap or QtGui.QPixmap())
         visible = True
         if visible:
             entry.overlay_frame.adjustSize()
+            entry.container.position_overlay()
             entry.overlay_frame.raise_()
         entry.overlay_frame.setVisible(visible)
 
@@ -438,7 +1200,7 @@ class PlayerWindow(QtWidgets.QMainWindow):
         entry = self._entries.get(channel)
         if entry is None:
             return
-        entry.audio_output.setVolume(0.0 if self._channel_muted[channel] else 1.0)
+        entry.audio_output.setVolume(self._volume_for_channel(channel))
         self._update_entry_overlay(entry)
 
     def _pixmap_for_icon(self, icon_name: str | None) -> QtGui.QPixmap | None:
@@ -457,6 +1219,55 @@ class PlayerWindow(QtWidgets.QMainWindow):
         return pixmap
 
 
+def _parse_crop(values: object) -> QtCore.QRectF | None:
+    # Crops are fractions of the frame, so they survive quality changes.
+    if not isinstance(values, dict):
+        return None
+    try:
+        x = float(values.get("x", 0.0))
+        y = float(values.get("y", 0.0))
+        width = float(values.get("width", 1.0 - x))
+        height = float(values.get("height", 1.0 - y))
+    except (TypeError, ValueError):
+        return None
+    if not (0.0 <= x < 1.0 and 0.0 <= y < 1.0 and width > 0 and height > 0):
+        return None
+    return QtCore.QRectF(x, y, min(width, 1.0 - x), min(height, 1.0 - y))
+
+
+def _native_window_exists(win_id: int) -> bool:
+    if sys.platform != "win32":
+        return win_id > 0
+    return bool(ctypes.windll.user32.IsWindow(ctypes.c_void_p(win_id)))
+
+
+def _metadata_label(metadata: StreamMetadata | None) -> str:
+    if metadata is None:
+        return ""
+    title = (metadata.title or "").strip()
+    if len(title) > 60:
+        title = title[:59].rstrip() + "…"
+    parts = [part for part in (metadata.category, title) if part]
+    if metadata.started_at is not None:
+        uptime = datetime.now(timezone.utc) - metadata.started_at
+        minutes = max(0, int(uptime.total_seconds() // 60))
+        parts.append(f"{minutes // 60}h{minutes % 60:02d}m")
+    if metadata.viewers is not None:
+        parts.append(f"{metadata.viewers:,} viewers")
+    return " · ".join(parts)
+
+
+def _audio_track_label(track: QtMultimedia.QMediaMetaData) -> str:
+    title = track.stringValue(QtMultimedia.QMediaMetaData.Title)
+    language = track.value(QtMultimedia.QMediaMetaData.Language)
+    language_name = ""
+    if isinstance(language, QtCore.QLocale.Language):
+        language_name = QtCore.QLocale.languageToString(language)
+    if title and language_name:
+        return f"{title} ({language_name})"
+    return title or language_name or "unnamed track"
+
+
 @dataclass(frozen=True)
 class _PlayerEntry:
     channel: str
@@ -464,8 +1275,9 @@ class _PlayerEntry:
     player: QtMultimedia.QMediaPlayer
     audio_output: QtMultimedia.QAudioOutput
     video_widget: _VideoSurface
-    container: QtWidgets.QWidget
+    container: _StreamContainer
     overlay_frame: QtWidgets.QFrame
     overlay_icon: QtWidgets.QLabel
     overlay_time: QtWidgets.QLabel
     overlay_name: QtWidgets.QLabel
+    overlay_meta: QtWidgets.QLabel
@@ -0,0 +1,131 @@
+import csv
+import io
+import json
+import time
+from pathlib import Path
+
+from .config import WATCH_STATS_FILE
+
+STATS_FORMATS = ("table", "json", "csv")
+
+
+class WatchTimeTracker:
+    def __init__(self, stats_file: Path | None = None) -> None:
+        self._stats_file = stats_file or WATCH_STATS_FILE
+        self._stats = load_watch_stats(self._stats_file)
+        self._displayed: list[str] = []
+        self._focused_channel: str | None = None
+        self._last_update = time.monotonic()
+
+    def update(self, displayed: list[str], focused: bool) -> None:
+        self._accumulate()
+        self._displayed = list(displayed)
+        self._focused_channel = displayed[0] if focused and displayed else None
+
+    def flush(self) -> None:
+        self._accumulate()
+        save_watch_stats(self._stats, self._stats_file)
+
+    def _accumulate(self) -> None:
+        now = time.monotonic()
+        elapsed = max(0.0, now - self._last_update)
+        self._last_update = now
+        if elapsed <= 0:
+            return
+        for channel in self._displayed:
+            channel_stats = self._stats.setdefault(
+                channel.lower(),
+                {"displayed_sec": 0.0, "focused_sec": 0.0},
+            )
+            channel_stats["displayed_sec"] += elapsed
+            if channel == self._focused_channel:
+                channel_stats["focused_sec"] += elapsed
+
+
+def load_watch_stats(
+    stats_file: Path | None = None,
+) -> dict[str, dict[str, float]]:
+    target = stats_file or WATCH_STATS_FILE
+    if not target.exists():
+        return {}
+    try:
+        payload = json.loads(target.read_text(encoding="utf-8"))
+    except (OSError, json.JSONDecodeError):
+        return {}
+    channels = payload.get("channels") if isinstance(payload, dict) else None
+    if not isinstance(channels, dict):
+        return {}
+    stats: dict[str, dict[str, float]] = {}
+    for channel, values in channels.items():
+        if not isinstance(values, dict):
+            continue
+        try:
+            stats[str(channel).lower()] = {
+                "displayed_sec": float(values.get("displayed_sec", 0.0)),
+                "focused_sec": float(values.get("focused_sec", 0.0)),
+            }
+        except (TypeError, ValueError):
+            continue
+    return stats
+
+
+def save_watch_stats(
+    stats: dict[str, dict[str, float]],
+    stats_file: Path | None = None,
+) -> None:
+    target = stats_file or WATCH_STATS_FILE
+    payload = {"channels": stats}
+    target.write_text(json.dumps(payload, indent=2), encoding="utf-8")
+
+
+def format_watch_stats(
+    stats: dict[str, dict[str, float]],
+    output_format: str = "table",
+) -> str:
+    rows = sorted(
+        stats.items(),
+        key=lambda item: (-item[1]["displayed_sec"], item[0]),
+    )
+    if output_format == "json":
+        return json.dumps(
+            [
+                {
+                    "channel": channel,
+                    "displayed_sec": round(values["displayed_sec"], 1),
+                    "focused_sec": round(values["focused_sec"], 1),
+                }
+                for channel, values in rows
+            ],
+            indent=2,
+        )
+    if output_format == "csv":
+        buffer = io.StringIO()
+        writer = csv.writer(buffer, lineterminator="\n")
+        writer.writerow(["channel", "displayed_sec", "focused_sec"])
+        for channel, values in rows:
+            writer.writerow(
+                [
+                    channel,
+                    f"{values['displayed_sec']:.1f}",
+                    f"{values['focused_sec']:.1f}",
+                ]
+            )
+        return buffer.getvalue().rstrip("\n")
+    if not rows:
+        return "No watch time recorded yet."
+    width = max(len("Channel"), *(len(channel) for channel, _ in rows))
+    lines = [f"{'Channel':<{width}}  {'Displayed':>10}  {'Focused':>10}"]
+    for channel, values in rows:
+        lines.append(
+            f"{channel:<{width}}  "
+            f"{_format_duration(values['displayed_sec']):>10}  "
+            f"{_format_duration(values['focused_sec']):>10}"
+        )
+    return "\n".join(lines)
+
+
+def _format_duration(seconds: float) -> str:
+    total_seconds = max(0, int(seconds))
+    hours, remainder = divmod(total_seconds, 3600)
+    minutes, seconds = divmod(remainder, 60)
+    return f"{hours}:{minutes:02d}:{seconds:02d}"
@@ -0,0 +1,31 @@
+import json
+import urllib.request
+
+from .history import history_entry
+from .perf_log import log_perf
+
+
+def post_webhook_event(
+    urls: list[str],
+    event: str,
+    timeout: float = 5.0,
+    **fields: object,
+) -> None:
+    body = json.dumps(history_entry(event, **fields)).encode("utf-8")
+    for url in urls:
+        request = urllib.request.Request(
+            url,
+            data=body,
+            headers={"Content-Type": "application/json", "User-Agent": "trs"},
+            method="POST",
+        )
+        try:
+            with urllib.request.urlopen(request, timeout=timeout):
+                pass
+        except Exception as exc:
+            log_perf(
+                "webhooks.post_failed",
+                webhook_event=event,
+                error=type(exc).__name__,
+            )
+            print(f"webhook post to '{url}' failed: {exc}")